@@ -0,0 +1,119 @@
+use std::env;
+use std::fs::OpenOptions;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+use base64::{engine::general_purpose::STANDARD, Engine};
+
+/// Command run with the text on its standard input instead of asking the terminal to copy,
+/// e.g. `wl-copy` or `xclip -selection clipboard`.
+pub const CLIPBOARD_COMMAND_VARIABLE: &str = "RN_CLIPBOARD_COMMAND";
+
+/// Puts text on the clipboard with `$RN_CLIPBOARD_COMMAND`, or else the configured command.
+/// Without either the OSC 52 escape sequence is used, which the terminal handles itself and
+/// so also works over SSH, though not every terminal supports it.
+pub fn copy(text: &str, configured_command: Option<&str>) -> Result<(), String> {
+    let command = env::var(CLIPBOARD_COMMAND_VARIABLE)
+        .ok()
+        .or(configured_command.map(String::from))
+        .filter(|command| !command.trim().is_empty());
+
+    if let Some(command) = command {
+        return copy_with_command(&command, text);
+    }
+
+    let in_tmux = env::var_os("TMUX").is_some();
+
+    let terminal_error = match OpenOptions::new().write(true).open("/dev/tty") {
+        Ok(mut terminal) => match terminal.write_all(osc52_sequence(text, in_tmux).as_bytes()) {
+            Ok(()) => return Ok(()),
+            Err(e) => e.to_string(),
+        },
+        Err(e) => e.to_string(),
+    };
+
+    Err(format!(
+        "Unable to reach the terminal ({}), set {} or clipboard_command in the configuration to copy with a command instead.",
+        terminal_error, CLIPBOARD_COMMAND_VARIABLE
+    ))
+}
+
+/// Inside tmux the sequence is wrapped so tmux passes it on to the outer terminal, which
+/// needs `set -g allow-passthrough on` in tmux 3.3 and newer.
+fn osc52_sequence(text: &str, in_tmux: bool) -> String {
+    let sequence = format!("\x1b]52;c;{}\x07", STANDARD.encode(text));
+
+    if in_tmux {
+        format!("\x1bPtmux;{}\x1b\\", sequence.replace('\x1b', "\x1b\x1b"))
+    } else {
+        sequence
+    }
+}
+
+fn copy_with_command(command: &str, text: &str) -> Result<(), String> {
+    let mut child = match Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .spawn()
+    {
+        Ok(c) => c,
+        Err(e) => return Err(format!("Unable to run '{}': {}", command, e)),
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(e) = stdin.write_all(text.as_bytes()) {
+            return Err(format!("Unable to write to '{}': {}", command, e));
+        }
+    }
+
+    match child.wait() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("'{}' exited with error: {}", command, status)),
+        Err(e) => Err(format!("Unable to run '{}': {}", command, e)),
+    }
+}
+
+#[allow(unused_variables)]
+mod clipboard_tests {
+    #[allow(unused_imports)]
+    use crate::clipboard::{copy, copy_with_command, osc52_sequence};
+    #[allow(unused_imports)]
+    use std::fs;
+
+    #[test]
+    fn test_osc52_sequence() {
+        assert_eq!(
+            osc52_sequence("ssh-add -l", false),
+            "\x1b]52;c;c3NoLWFkZCAtbA==\x07"
+        );
+    }
+
+    #[test]
+    fn test_osc52_sequence_in_tmux() {
+        assert_eq!(
+            osc52_sequence("ssh-add -l", true),
+            "\x1bPtmux;\x1b\x1b]52;c;c3NoLWFkZCAtbA==\x07\x1b\\"
+        );
+    }
+
+    #[test]
+    fn test_copy_with_command() {
+        fs::create_dir_all("./rn-tests-data").unwrap();
+        let path = "./rn-tests-data/clipboardtest";
+
+        copy_with_command(&format!("cat > {}", path), "first line\nsecond line").unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "first line\nsecond line");
+        assert!(copy_with_command("false", "text").is_err());
+    }
+
+    #[test]
+    fn test_configured_command_comes_first() {
+        fs::create_dir_all("./rn-tests-data").unwrap();
+        let path = "./rn-tests-data/clipboardconfigtest";
+        let _ = fs::remove_file(path);
+
+        copy("ssh-add -l", Some(&format!("cat > {}", path))).unwrap();
+        assert_eq!(fs::read_to_string(path).unwrap(), "ssh-add -l");
+    }
+}
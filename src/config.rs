@@ -0,0 +1,277 @@
+use std::collections::HashMap;
+use std::fs;
+use std::path::Path;
+
+use serde::Deserialize;
+
+use crate::masking::Masker;
+use crate::parsearguments::CommandType;
+use crate::terminal::ColorChoice;
+
+pub const CONFIG_FILE: &str = "config.toml";
+
+const DEFAULT_LIST_FORMAT: &str = "{index}: {text}";
+
+const DEFAULT_INBOX_FILE: &str = "inbox";
+
+/// Settings read from `config.toml`, every one of them optional.
+#[derive(Debug, Default, PartialEq, Eq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct Config {
+    /// Note file that `rn "text"` adds to when no note file is called "text". It is also the
+    /// inbox that `rn - "text"` adds to, which is called `inbox` when this is not set.
+    pub default_file: Option<String>,
+    /// Editor used instead of `$VISUAL` and `$EDITOR`.
+    pub editor: Option<String>,
+    /// How a note is listed, with `{index}` and `{text}` filled in.
+    pub list_format: Option<String>,
+    /// Whether listings are colored, overridden by `--color`.
+    pub color: Option<ColorChoice>,
+    /// Pager for listings that do not fit the terminal, instead of `$PAGER`. An empty
+    /// string turns paging off.
+    pub pager: Option<String>,
+    pub confirm: ConfirmPolicy,
+    /// Used for copying instead of the terminal, unless `$RN_CLIPBOARD_COMMAND` is set.
+    pub clipboard_command: Option<String>,
+    /// Regular expressions for secrets to mask besides the built-in ones.
+    pub secret_patterns: Vec<String>,
+    /// Words that stand for a longer command, e.g. `t = "todo add"`.
+    pub aliases: HashMap<String, String>,
+}
+
+/// Which changes to the notebook are asked about before they are made.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ConfirmPolicy {
+    #[default]
+    Never,
+    Remove,
+    Always,
+}
+
+impl ConfirmPolicy {
+    pub fn requires_confirmation(&self, command_type: &CommandType) -> bool {
+        match self {
+            ConfirmPolicy::Never => false,
+            ConfirmPolicy::Remove => matches!(
+                command_type,
+                CommandType::RemoveNoteFile(_) | CommandType::RemoveNoteFromFile(_, _)
+            ),
+            ConfirmPolicy::Always => true,
+        }
+    }
+}
+
+impl Config {
+    /// Reads `config.toml` from the notes directory, or failing that from `rn/` in the XDG
+    /// config directory. Without either file every setting has its default.
+    pub fn load(working_directory: &str) -> Result<Config, String> {
+        let candidates = [
+            Some(Path::new(working_directory).join(CONFIG_FILE)),
+            dirs::config_dir().map(|dir| dir.join("rn").join(CONFIG_FILE)),
+        ];
+
+        for path in candidates.into_iter().flatten() {
+            if let Ok(content) = fs::read_to_string(&path) {
+                return match Config::parse(&content) {
+                    Ok(config) => Ok(config),
+                    Err(e) => Err(format!("{}: {}", path.display(), e)),
+                };
+            }
+        }
+
+        Ok(Config::default())
+    }
+
+    pub fn parse(content: &str) -> Result<Config, String> {
+        let config: Config = match toml::from_str(content) {
+            Ok(config) => config,
+            Err(e) => return Err(e.message().to_string()),
+        };
+        config.masker()?;
+
+        Ok(config)
+    }
+
+    /// Masker for the built-in secret patterns and those in `secret_patterns`.
+    pub fn masker(&self) -> Result<Masker, String> {
+        Masker::new().with_patterns(&self.secret_patterns)
+    }
+
+    pub fn inbox_file(&self) -> &str {
+        self.default_file.as_deref().unwrap_or(DEFAULT_INBOX_FILE)
+    }
+
+    /// Replaces an alias given as the first argument with the words it stands for.
+    pub fn expand_aliases(&self, mut args: Vec<String>) -> Vec<String> {
+        if let Some(expansion) = args.get(1).and_then(|first| self.aliases.get(first)) {
+            let words: Vec<String> = expansion.split_whitespace().map(String::from).collect();
+            args.splice(1..2, words);
+        }
+
+        args
+    }
+
+    /// `rn <text>` adds the text to the default file when there is no note file by that name.
+    pub fn apply_default_file(
+        &self,
+        args: &[String],
+        command_type: CommandType,
+        working_directory: &str,
+    ) -> CommandType {
+        match (command_type, &self.default_file) {
+            (CommandType::ListNotesInFile(text, _), Some(default_file))
+                if args.len() == 2 && !Path::new(working_directory).join(&text).exists() =>
+            {
+                CommandType::AddNoteToFile(default_file.clone(), text)
+            }
+            (command_type, _) => command_type,
+        }
+    }
+
+    /// The listing format split around the note text, with the index filled in.
+    pub fn list_prefix_and_suffix(&self, index: &str) -> (String, String) {
+        let format = self.list_format.as_deref().unwrap_or(DEFAULT_LIST_FORMAT);
+        let (prefix, suffix) = format.split_once("{text}").unwrap_or((format, ""));
+
+        (
+            prefix.replace("{index}", index),
+            suffix.replace("{index}", index),
+        )
+    }
+}
+
+#[allow(unused_variables)]
+mod config_tests {
+    #[allow(unused_imports)]
+    use crate::config::{Config, ConfirmPolicy};
+    #[allow(unused_imports)]
+    use crate::parsearguments::{CommandType, ListOptions};
+    #[allow(unused_imports)]
+    use crate::selection::{NoteIndex, NoteSelector};
+    #[allow(unused_imports)]
+    use crate::terminal::ColorChoice;
+    #[allow(unused_imports)]
+    use std::collections::HashMap;
+
+    #[allow(dead_code)]
+    fn args(words: &[&str]) -> Vec<String> {
+        words.iter().map(|word| word.to_string()).collect()
+    }
+
+    #[test]
+    fn test_parse_config() {
+        let config = Config::parse(
+            "default_file = \"inbox\"\n\
+             editor = \"nvim\"\n\
+             confirm = \"remove\"\n\
+             color = \"never\"\n\
+             pager = \"\"\n\
+             secret_patterns = [\"corp-[0-9a-f]{8}\"]\n\
+             [aliases]\n\
+             t = \"todo add\"\n",
+        )
+        .unwrap();
+
+        assert_eq!(config.default_file, Some("inbox".to_string()));
+        assert_eq!(config.editor, Some("nvim".to_string()));
+        assert_eq!(config.confirm, ConfirmPolicy::Remove);
+        assert_eq!(config.color, Some(ColorChoice::Never));
+        assert_eq!(config.pager, Some("".to_string()));
+        assert_eq!(config.secret_patterns, vec!["corp-[0-9a-f]{8}".to_string()]);
+        assert_eq!(config.aliases.get("t"), Some(&"todo add".to_string()));
+        assert_eq!(Config::parse(""), Ok(Config::default()));
+    }
+
+    #[test]
+    fn test_invalid_config() {
+        assert!(Config::parse("colour = \"always\"").is_err());
+        assert!(Config::parse("confirm = \"sometimes\"").is_err());
+        assert!(Config::parse("default_file = 3").is_err());
+        assert!(Config::parse("secret_patterns = [\"(unclosed\"]").is_err());
+    }
+
+    #[test]
+    fn test_expand_aliases() {
+        let config = Config {
+            aliases: HashMap::from([("t".to_string(), "todo add".to_string())]),
+            ..Config::default()
+        };
+
+        assert_eq!(
+            config.expand_aliases(args(&["rn", "t", "buy milk"])),
+            args(&["rn", "todo", "add", "buy milk"])
+        );
+        assert_eq!(
+            config.expand_aliases(args(&["rn", "todo", "t"])),
+            args(&["rn", "todo", "t"])
+        );
+        assert_eq!(config.expand_aliases(args(&["rn"])), args(&["rn"]));
+    }
+
+    #[test]
+    fn test_apply_default_file() {
+        let config = Config {
+            default_file: Some("inbox".to_string()),
+            ..Config::default()
+        };
+        let text =
+            CommandType::ListNotesInFile("call the bank".to_string(), ListOptions::default());
+
+        assert_eq!(
+            config.apply_default_file(
+                &args(&["rn", "call the bank"]),
+                text.clone(),
+                "./rn-tests-data/"
+            ),
+            CommandType::AddNoteToFile("inbox".to_string(), "call the bank".to_string())
+        );
+        assert_eq!(
+            config.apply_default_file(
+                &args(&["rn", "call the bank", "list"]),
+                text.clone(),
+                "./rn-tests-data/"
+            ),
+            text
+        );
+        assert_eq!(
+            Config::default().apply_default_file(
+                &args(&["rn", "call the bank"]),
+                text.clone(),
+                "./rn-tests-data/"
+            ),
+            text
+        );
+    }
+
+    #[test]
+    fn test_confirm_policy() {
+        let remove = CommandType::RemoveNoteFromFile(
+            "notefile".to_string(),
+            NoteSelector::single(NoteIndex::FromStart(0)),
+        );
+        let add = CommandType::AddNoteToFile("notefile".to_string(), "note".to_string());
+
+        assert!(!ConfirmPolicy::Never.requires_confirmation(&remove));
+        assert!(ConfirmPolicy::Remove.requires_confirmation(&remove));
+        assert!(!ConfirmPolicy::Remove.requires_confirmation(&add));
+        assert!(ConfirmPolicy::Always.requires_confirmation(&add));
+    }
+
+    #[test]
+    fn test_list_format() {
+        assert_eq!(
+            Config::default().list_prefix_and_suffix("3"),
+            ("3: ".to_string(), "".to_string())
+        );
+
+        let config = Config {
+            list_format: Some("[{index}] {text} <".to_string()),
+            ..Config::default()
+        };
+        assert_eq!(
+            config.list_prefix_and_suffix("3"),
+            ("[3] ".to_string(), " <".to_string())
+        );
+    }
+}
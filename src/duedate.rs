@@ -0,0 +1,111 @@
+use chrono::{Datelike, Days, NaiveDate, Weekday};
+
+pub const DATE_FORMAT: &str = "%Y-%m-%d";
+
+/// Reads a due date given as `2026-11-01`, `today`, `tomorrow`, a weekday such as `friday`
+/// (the next one after today), `next week`, `in 3 days`, `in 2 weeks`, `+3d` or `+2w`.
+pub fn parse_due_date(value: &str, today: NaiveDate) -> Result<NaiveDate, String> {
+    let value = value.trim().to_lowercase();
+    let error = || {
+        format!(
+            "Unable to read due date '{}', expected e.g. 2026-11-01, tomorrow, friday or in 3 days",
+            value
+        )
+    };
+
+    if let Ok(date) = NaiveDate::parse_from_str(&value, DATE_FORMAT) {
+        return Ok(date);
+    }
+
+    let days = match value.as_str() {
+        "today" => 0,
+        "tomorrow" => 1,
+        "next week" => 7,
+        _ => {
+            if let Ok(weekday) = value.parse::<Weekday>() {
+                let ahead = (7 + weekday.num_days_from_monday()
+                    - today.weekday().num_days_from_monday())
+                    % 7;
+                if ahead == 0 {
+                    7
+                } else {
+                    ahead as u64
+                }
+            } else {
+                relative_days(&value).ok_or_else(error)?
+            }
+        }
+    };
+
+    today.checked_add_days(Days::new(days)).ok_or_else(error)
+}
+
+/// Days in `in 3 days`, `in 2 weeks`, `+3d` or `+2w`.
+fn relative_days(value: &str) -> Option<u64> {
+    let (count, unit) = if let Some(rest) = value.strip_prefix("in ") {
+        rest.trim().split_once(' ')?
+    } else {
+        let rest = value.strip_prefix('+')?;
+        rest.split_at(rest.find(|c: char| !c.is_ascii_digit())?)
+    };
+
+    let count = count.trim().parse::<u64>().ok()?;
+
+    match unit.trim() {
+        "d" | "day" | "days" => Some(count),
+        "w" | "week" | "weeks" => Some(count * 7),
+        _ => None,
+    }
+}
+
+#[allow(unused_variables)]
+mod duedate_tests {
+    #[allow(unused_imports)]
+    use crate::duedate::parse_due_date;
+    #[allow(unused_imports)]
+    use chrono::NaiveDate;
+
+    #[allow(dead_code)]
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_parse_dates() {
+        // 2026-10-19 is a Monday
+        let today = date(2026, 10, 19);
+
+        assert_eq!(parse_due_date("2026-11-01", today), Ok(date(2026, 11, 1)));
+        assert_eq!(parse_due_date("today", today), Ok(today));
+        assert_eq!(parse_due_date("Tomorrow", today), Ok(date(2026, 10, 20)));
+        assert_eq!(parse_due_date("next week", today), Ok(date(2026, 10, 26)));
+    }
+
+    #[test]
+    fn test_parse_weekdays() {
+        let today = date(2026, 10, 19);
+
+        assert_eq!(parse_due_date("friday", today), Ok(date(2026, 10, 23)));
+        assert_eq!(parse_due_date("sun", today), Ok(date(2026, 10, 25)));
+        assert_eq!(parse_due_date("monday", today), Ok(date(2026, 10, 26)));
+    }
+
+    #[test]
+    fn test_parse_relative_dates() {
+        let today = date(2026, 10, 19);
+
+        assert_eq!(parse_due_date("in 3 days", today), Ok(date(2026, 10, 22)));
+        assert_eq!(parse_due_date("in 2 weeks", today), Ok(date(2026, 11, 2)));
+        assert_eq!(parse_due_date("+1d", today), Ok(date(2026, 10, 20)));
+        assert_eq!(parse_due_date("+1w", today), Ok(date(2026, 10, 26)));
+    }
+
+    #[test]
+    fn test_invalid_dates() {
+        let today = date(2026, 10, 19);
+
+        for value in ["someday", "2026-13-01", "in x days", "+3y", "+d", ""] {
+            assert!(parse_due_date(value, today).is_err(), "{}", value);
+        }
+    }
+}
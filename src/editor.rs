@@ -0,0 +1,212 @@
+use std::env;
+use std::fs::{self, DirBuilder, OpenOptions};
+use std::io::Write;
+use std::os::unix::fs::{DirBuilderExt, OpenOptionsExt};
+use std::path::{Path, PathBuf};
+use std::process::{self, Command};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+/// Editors that take the line to jump to as `--goto file:line` instead of `+line file`.
+const GOTO_EDITORS: [&str; 4] = ["code", "code-insiders", "codium", "code-oss"];
+
+/// Directory kept in memory where decrypted notes can be edited when `$XDG_RUNTIME_DIR` is
+/// not set.
+const SHARED_MEMORY_DIRECTORY: &str = "/dev/shm";
+
+/// Private directories created so far, so that edits running at once each get their own.
+static DIRECTORY_COUNT: AtomicUsize = AtomicUsize::new(0);
+
+/// Opens a file in the editor, with the cursor placed on the given line (1-based). The
+/// configured editor is used before `$VISUAL` and `$EDITOR`.
+pub fn edit_file(configured: Option<&str>, path: &str, line: Option<usize>) -> Result<(), String> {
+    let (editor, mut args) = editor_command(configured)?;
+    wait_for_editor(&editor, &mut args);
+    match line {
+        Some(line) => args.extend(line_arguments(&editor, path, line)),
+        None => args.push(path.to_string()),
+    }
+    run_editor(&editor, &args)
+}
+
+/// Edits text through a file only the user can read, which is removed once the editor is
+/// done.
+pub fn edit_text(configured: Option<&str>, text: &str) -> Result<String, String> {
+    edit_copy(configured, text, None, PrivateDirectory::create(false)?)
+}
+
+/// Edits text that must not reach the disk, such as the notes of an encrypted file. The
+/// text goes to a file only the user can read, in a directory kept in memory, which is
+/// removed once the editor is done. `line` places the cursor (1-based).
+pub fn edit_privately(
+    configured: Option<&str>,
+    text: &str,
+    line: Option<usize>,
+) -> Result<String, String> {
+    edit_copy(configured, text, line, PrivateDirectory::create(true)?)
+}
+
+fn edit_copy(
+    configured: Option<&str>,
+    text: &str,
+    line: Option<usize>,
+    directory: PrivateDirectory,
+) -> Result<String, String> {
+    let path = directory.0.join("notes");
+    let path = path.to_string_lossy();
+
+    let written = OpenOptions::new()
+        .write(true)
+        .create_new(true)
+        .mode(0o600)
+        .open(path.as_ref())
+        .and_then(|mut file| file.write_all(text.as_bytes()));
+
+    if let Err(e) = written {
+        return Err(format!("Unable to write {}: {}", path, e));
+    }
+
+    edit_file(configured, &path, line)?;
+
+    match fs::read_to_string(path.as_ref()) {
+        Ok(edited) => Ok(edited),
+        Err(e) => Err(format!("Unable to read {}: {}", path, e)),
+    }
+}
+
+fn run_editor(editor: &str, args: &[String]) -> Result<(), String> {
+    match Command::new(editor).args(args).status() {
+        Ok(status) if status.success() => Ok(()),
+        Ok(status) => Err(format!("Editor '{}' exited with error: {}", editor, status)),
+        Err(e) => Err(format!("Unable to start editor '{}': {}", editor, e)),
+    }
+}
+
+/// A directory only the user can enter, removed with everything in it when dropped.
+struct PrivateDirectory(PathBuf);
+
+impl PrivateDirectory {
+    /// Creates the directory in `$XDG_RUNTIME_DIR` or `/dev/shm`, or unless `in_memory` is
+    /// set, in the temporary directory when neither is there.
+    fn create(in_memory: bool) -> Result<PrivateDirectory, String> {
+        let temporary = (!in_memory).then(env::temp_dir);
+        let parent = env::var_os("XDG_RUNTIME_DIR")
+            .map(PathBuf::from)
+            .into_iter()
+            .chain([PathBuf::from(SHARED_MEMORY_DIRECTORY)])
+            .chain(temporary)
+            .find(|directory| directory.is_dir());
+
+        let parent = match parent {
+            Some(parent) => parent,
+            None => {
+                return Err(format!(
+                    "No directory kept in memory to edit decrypted notes in, set $XDG_RUNTIME_DIR or mount {}.",
+                    SHARED_MEMORY_DIRECTORY
+                ))
+            }
+        };
+
+        let count = DIRECTORY_COUNT.fetch_add(1, Ordering::Relaxed);
+        let path = parent.join(format!("rn-{}-{}", process::id(), count));
+
+        match DirBuilder::new().mode(0o700).create(&path) {
+            Ok(()) => Ok(PrivateDirectory(path)),
+            Err(e) => Err(format!("Unable to create {}: {}", path.display(), e)),
+        }
+    }
+}
+
+impl Drop for PrivateDirectory {
+    fn drop(&mut self) {
+        let _ = fs::remove_dir_all(&self.0);
+    }
+}
+
+/// Resolves the editor: the configured one, then the same way the edit crate does,
+/// `$VISUAL`, then `$EDITOR`, then whatever default editor the system has.
+fn editor_command(configured: Option<&str>) -> Result<(String, Vec<String>), String> {
+    let commands = [
+        configured.map(String::from),
+        env::var("VISUAL").ok(),
+        env::var("EDITOR").ok(),
+    ];
+
+    for command in commands.into_iter().flatten() {
+        let mut words = command.split_whitespace().map(String::from);
+
+        if let Some(program) = words.next() {
+            return Ok((program, words.collect()));
+        }
+    }
+
+    match edit::get_editor() {
+        Ok(path) => Ok((path.to_string_lossy().into_owned(), Vec::new())),
+        Err(e) => Err(format!("Unable to find an editor: {}", e)),
+    }
+}
+
+fn line_arguments(editor: &str, path: &str, line: usize) -> Vec<String> {
+    if is_goto_editor(editor) {
+        vec![String::from("--goto"), format!("{}:{}", path, line)]
+    } else {
+        vec![format!("+{}", line), path.to_string()]
+    }
+}
+
+/// VS Code style editors return as soon as the file is open in a window, so they are told
+/// to wait until it is closed unless `$VISUAL` already does.
+fn wait_for_editor(editor: &str, args: &mut Vec<String>) {
+    if is_goto_editor(editor) && !args.iter().any(|arg| arg == "--wait" || arg == "-w") {
+        args.push(String::from("--wait"));
+    }
+}
+
+fn is_goto_editor(editor: &str) -> bool {
+    let program = Path::new(editor)
+        .file_stem()
+        .and_then(|name| name.to_str())
+        .unwrap_or(editor);
+
+    GOTO_EDITORS.contains(&program)
+}
+
+#[allow(unused_variables)]
+mod editor_tests {
+    #[allow(unused_imports)]
+    use crate::editor::{line_arguments, wait_for_editor};
+
+    #[test]
+    fn test_line_arguments_for_terminal_editors() {
+        assert_eq!(line_arguments("vim", "notes", 4), vec!["+4", "notes"]);
+        assert_eq!(
+            line_arguments("/usr/bin/nano", "notes", 1),
+            vec!["+1", "notes"]
+        );
+        assert_eq!(line_arguments("emacs", "notes", 12), vec!["+12", "notes"]);
+    }
+
+    #[test]
+    fn test_line_arguments_for_vscode() {
+        assert_eq!(
+            line_arguments("code", "notes", 4),
+            vec!["--goto", "notes:4"]
+        );
+        assert_eq!(
+            line_arguments("/usr/local/bin/codium", "notes", 7),
+            vec!["--goto", "notes:7"]
+        );
+    }
+
+    #[test]
+    fn test_vscode_waits() {
+        let mut args = Vec::new();
+        wait_for_editor("/usr/bin/code", &mut args);
+        assert_eq!(args, vec!["--wait"]);
+        wait_for_editor("code", &mut args);
+        assert_eq!(args, vec!["--wait"]);
+
+        let mut args = Vec::new();
+        wait_for_editor("vim", &mut args);
+        assert!(args.is_empty());
+    }
+}
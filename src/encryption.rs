@@ -0,0 +1,173 @@
+//! Encrypted note files start with a marker line followed by a single base64 line holding
+//! the salt, the nonce and the XChaCha20-Poly1305 ciphertext of the plain note file. The key
+//! is derived from the passphrase or key file with Argon2id. The marker line and the salt
+//! are authenticated along with the ciphertext.
+
+use argon2::Argon2;
+use base64::{engine::general_purpose::STANDARD, Engine};
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, AeadCore, KeyInit, OsRng, Payload};
+use chacha20poly1305::{XChaCha20Poly1305, XNonce};
+
+const MARKER: &str = "rn-encrypted-v1\n";
+const SALT_LENGTH: usize = 16;
+const NONCE_LENGTH: usize = 24;
+const KEY_LENGTH: usize = 32;
+
+/// Key derived from a passphrase, along with the salt it was derived with.
+pub struct Key {
+    salt: [u8; SALT_LENGTH],
+    key: [u8; KEY_LENGTH],
+}
+
+impl Key {
+    /// Derives a key with a new random salt, for encrypting.
+    pub fn generate(secret: &[u8]) -> Result<Key, String> {
+        let mut salt = [0u8; SALT_LENGTH];
+        OsRng.fill_bytes(&mut salt);
+        Key::derive(secret, salt)
+    }
+
+    /// Derives the key that `content` was encrypted with.
+    pub fn for_content(secret: &[u8], content: &str) -> Result<Key, String> {
+        Key::derive(secret, salt_of(content)?)
+    }
+
+    pub fn fits(&self, content: &str) -> bool {
+        salt_of(content).is_ok_and(|salt| salt == self.salt)
+    }
+
+    fn derive(secret: &[u8], salt: [u8; SALT_LENGTH]) -> Result<Key, String> {
+        let mut key = [0u8; KEY_LENGTH];
+
+        match Argon2::default().hash_password_into(secret, &salt, &mut key) {
+            Ok(()) => Ok(Key { salt, key }),
+            Err(e) => Err(format!("Unable to derive encryption key: {}", e)),
+        }
+    }
+}
+
+pub fn is_encrypted(content: &str) -> bool {
+    content.starts_with(MARKER)
+}
+
+pub fn encrypt(plaintext: &str, key: &Key) -> Result<String, String> {
+    let cipher = XChaCha20Poly1305::new(&key.key.into());
+    let nonce = XChaCha20Poly1305::generate_nonce(&mut OsRng);
+
+    let ciphertext = match cipher.encrypt(
+        &nonce,
+        Payload {
+            msg: plaintext.as_bytes(),
+            aad: &associated_data(&key.salt),
+        },
+    ) {
+        Ok(c) => c,
+        Err(_) => return Err("Unable to encrypt note file.".to_string()),
+    };
+
+    let mut payload = Vec::with_capacity(SALT_LENGTH + NONCE_LENGTH + ciphertext.len());
+    payload.extend_from_slice(&key.salt);
+    payload.extend_from_slice(&nonce);
+    payload.extend_from_slice(&ciphertext);
+
+    Ok(format!("{}{}\n", MARKER, STANDARD.encode(payload)))
+}
+
+pub fn decrypt(content: &str, key: &Key) -> Result<String, String> {
+    let payload = decode_payload(content)?;
+    let (salt, rest) = payload.split_at(SALT_LENGTH);
+    let (nonce, ciphertext) = rest.split_at(NONCE_LENGTH);
+    let cipher = XChaCha20Poly1305::new(&key.key.into());
+
+    let plaintext = match cipher.decrypt(
+        XNonce::from_slice(nonce),
+        Payload {
+            msg: ciphertext,
+            aad: &associated_data(salt),
+        },
+    ) {
+        Ok(p) => p,
+        Err(_) => return Err("wrong passphrase or damaged file".to_string()),
+    };
+
+    match String::from_utf8(plaintext) {
+        Ok(p) => Ok(p),
+        Err(_) => Err("decrypted notes are not valid UTF-8".to_string()),
+    }
+}
+
+fn associated_data(salt: &[u8]) -> Vec<u8> {
+    let mut data = MARKER.as_bytes().to_vec();
+    data.extend_from_slice(salt);
+    data
+}
+
+fn salt_of(content: &str) -> Result<[u8; SALT_LENGTH], String> {
+    let payload = decode_payload(content)?;
+    let mut salt = [0u8; SALT_LENGTH];
+    salt.copy_from_slice(&payload[..SALT_LENGTH]);
+    Ok(salt)
+}
+
+fn decode_payload(content: &str) -> Result<Vec<u8>, String> {
+    let encoded = match content.strip_prefix(MARKER) {
+        Some(encoded) => encoded.trim(),
+        None => return Err("not an encrypted note file".to_string()),
+    };
+
+    match STANDARD.decode(encoded) {
+        Ok(payload) if payload.len() > SALT_LENGTH + NONCE_LENGTH => Ok(payload),
+        _ => Err("damaged encrypted note file".to_string()),
+    }
+}
+
+#[allow(unused_variables)]
+mod encryption_tests {
+    #[allow(unused_imports)]
+    use crate::encryption::{decrypt, encrypt, is_encrypted, Key};
+
+    #[test]
+    fn test_encrypt_and_decrypt() -> Result<(), String> {
+        let key = Key::generate(b"correct horse")?;
+        let content = encrypt("first note\nsecond note\n", &key)?;
+
+        assert!(is_encrypted(&content));
+        assert!(!content.contains("first note"));
+
+        let key = Key::for_content(b"correct horse", &content)?;
+        assert!(key.fits(&content));
+        assert_eq!(decrypt(&content, &key)?, "first note\nsecond note\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_wrong_passphrase() -> Result<(), String> {
+        let content = encrypt("secret note\n", &Key::generate(b"correct horse")?)?;
+        let key = Key::for_content(b"battery staple", &content)?;
+
+        assert_eq!(
+            decrypt(&content, &key),
+            Err("wrong passphrase or damaged file".to_string())
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_damaged_file() -> Result<(), String> {
+        let key = Key::generate(b"correct horse")?;
+        let content = encrypt("secret note\n", &key)?;
+        let position = content.len() - 5;
+        let replacement = if &content[position..position + 1] == "A" {
+            "B"
+        } else {
+            "A"
+        };
+        let mut damaged = content.clone();
+        damaged.replace_range(position..position + 1, replacement);
+
+        assert!(decrypt(&damaged, &key).is_err());
+        assert!(!is_encrypted("secret note\n"));
+        Ok(())
+    }
+}
@@ -0,0 +1,270 @@
+use std::cmp::Reverse;
+use std::fs;
+use std::path::Path;
+use std::time::SystemTime;
+
+use chrono::{DateTime, Local};
+
+use crate::config::CONFIG_FILE;
+use crate::encryption;
+use crate::journal::JOURNAL_DIRECTORY;
+use crate::notefile;
+
+/// Editor swap and backup files, and files left behind by interrupted writes.
+const TEMPORARY_SUFFIXES: [&str; 6] = ["~", ".swp", ".swo", ".tmp", ".bak", ".orig"];
+
+/// How `rn list` orders note files.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum FileSort {
+    #[default]
+    Name,
+    /// Most recently modified first.
+    Modified,
+    /// Most notes first.
+    Count,
+}
+
+impl FileSort {
+    pub fn parse(value: &str) -> Result<FileSort, String> {
+        match value {
+            "name" => Ok(FileSort::Name),
+            "mtime" => Ok(FileSort::Modified),
+            "count" => Ok(FileSort::Count),
+            _ => Err(format!(
+                "Unknown sort order: {}, expected name, mtime or count",
+                value
+            )),
+        }
+    }
+}
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ListFilesOptions {
+    pub long: bool,
+    pub sort: FileSort,
+}
+
+/// A note file with what `rn list --long` shows about it.
+#[derive(Debug, PartialEq, Eq)]
+pub struct NoteFileInfo {
+    pub name: String,
+    pub modified: SystemTime,
+    pub size: u64,
+    /// `None` for encrypted files, which are not decrypted just to count their notes, and
+    /// for files that are not valid note files.
+    pub note_count: Option<usize>,
+    pub encrypted: bool,
+}
+
+/// Whether a directory entry is a note file to list, leaving out hidden entries such as
+/// the lock file, git repository and note history, the configuration, and temporary and
+/// trash files.
+pub fn is_listed(file_name: &str) -> bool {
+    let hidden = file_name.starts_with('.');
+    let autosave = file_name.starts_with('#') && file_name.ends_with('#');
+    let temporary = TEMPORARY_SUFFIXES
+        .iter()
+        .any(|suffix| file_name.ends_with(suffix));
+    let trash = file_name.eq_ignore_ascii_case("trash");
+
+    !(hidden || autosave || temporary || trash || file_name == CONFIG_FILE)
+}
+
+/// Note files in the notes directory, ordered as asked. The files of the journal are named
+/// by their path, e.g. `journal/2026-10-18`. Other subdirectories are not looked into.
+pub fn note_files(working_directory: &str, sort: FileSort) -> Result<Vec<NoteFileInfo>, String> {
+    let mut files = Vec::new();
+    collect_note_files(working_directory, "", &mut files)?;
+
+    files.sort_by(|a, b| a.name.cmp(&b.name));
+
+    match sort {
+        FileSort::Name => {}
+        FileSort::Modified => files.sort_by_key(|file| Reverse(file.modified)),
+        FileSort::Count => files.sort_by_key(|file| Reverse(file.note_count)),
+    }
+
+    Ok(files)
+}
+
+/// Adds the note files of the directory `subdirectory` of the notes directory to `files`,
+/// and those of the journal when looking at the notes directory itself.
+fn collect_note_files(
+    working_directory: &str,
+    subdirectory: &str,
+    files: &mut Vec<NoteFileInfo>,
+) -> Result<(), String> {
+    let entries = match fs::read_dir(Path::new(working_directory).join(subdirectory)) {
+        Ok(entries) => entries,
+        Err(e) => return Err(e.to_string()),
+    };
+
+    for entry in entries.flatten() {
+        let file_name = entry.file_name().to_string_lossy().into_owned();
+        let name = if subdirectory.is_empty() {
+            file_name.clone()
+        } else {
+            format!("{}/{}", subdirectory, file_name)
+        };
+        let metadata = match entry.metadata() {
+            Ok(metadata) if is_listed(&file_name) => metadata,
+            _ => continue,
+        };
+
+        if metadata.is_dir() {
+            // A journal that can not be read leaves the other note files to list
+            if subdirectory.is_empty() && file_name == JOURNAL_DIRECTORY {
+                let _ = collect_note_files(working_directory, &name, files);
+            }
+            continue;
+        } else if !metadata.is_file() {
+            continue;
+        }
+
+        let content = fs::read_to_string(Path::new(working_directory).join(&name));
+        let encrypted = content
+            .as_ref()
+            .is_ok_and(|content| encryption::is_encrypted(content));
+        let note_count = match content {
+            Ok(content) if !encrypted => notefile::parse_notes(&content)
+                .ok()
+                .map(|notes| notes.len()),
+            _ => None,
+        };
+
+        files.push(NoteFileInfo {
+            name,
+            modified: metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH),
+            size: metadata.len(),
+            note_count,
+            encrypted,
+        });
+    }
+
+    Ok(())
+}
+
+/// The note count, modification time and size of a file, lined up in columns when the
+/// counts are padded to `count_width`.
+pub fn long_details(file: &NoteFileInfo, count_width: usize) -> String {
+    let notes = match (file.note_count, file.encrypted) {
+        (Some(1), _) => format!("{:>width$} note ", 1, width = count_width),
+        (Some(count), _) => format!("{:>width$} notes", count, width = count_width),
+        (None, true) => format!("{:>width$}", "encrypted", width = count_width + 6),
+        (None, false) => format!("{:>width$}", "invalid", width = count_width + 6),
+    };
+    let modified = DateTime::<Local>::from(file.modified).format("%Y-%m-%d %H:%M");
+
+    format!("{}  {}  {:>9}", notes, modified, format_size(file.size))
+}
+
+fn format_size(size: u64) -> String {
+    const UNITS: [&str; 3] = ["KiB", "MiB", "GiB"];
+
+    if size < 1024 {
+        return format!("{} B", size);
+    }
+
+    let mut value = size as f64 / 1024.0;
+    let mut unit = 0;
+
+    while value >= 1024.0 && unit < UNITS.len() - 1 {
+        value /= 1024.0;
+        unit += 1;
+    }
+
+    format!("{:.1} {}", value, UNITS[unit])
+}
+
+#[allow(unused_variables)]
+mod filelisting_tests {
+    #[allow(unused_imports)]
+    use crate::filelisting::{
+        format_size, is_listed, long_details, note_files, FileSort, NoteFileInfo,
+    };
+    #[allow(unused_imports)]
+    use std::fs;
+    #[allow(unused_imports)]
+    use std::time::SystemTime;
+
+    #[test]
+    fn test_is_listed() {
+        for name in ["todo", "ssh_notes", "2026-10-19"] {
+            assert!(is_listed(name), "{}", name);
+        }
+        for name in [
+            ".rn.lock",
+            ".git",
+            ".history",
+            "config.toml",
+            "todo~",
+            ".todo.swp",
+            "todo.tmp",
+            "#todo#",
+            "Trash",
+        ] {
+            assert!(!is_listed(name), "{}", name);
+        }
+    }
+
+    #[test]
+    fn test_format_size() {
+        assert_eq!(format_size(0), "0 B");
+        assert_eq!(format_size(1023), "1023 B");
+        assert_eq!(format_size(1536), "1.5 KiB");
+        assert_eq!(format_size(5 * 1024 * 1024), "5.0 MiB");
+    }
+
+    #[test]
+    fn test_long_details() {
+        let file = NoteFileInfo {
+            name: "todo".to_string(),
+            modified: SystemTime::UNIX_EPOCH,
+            size: 20,
+            note_count: Some(12),
+            encrypted: false,
+        };
+
+        assert!(long_details(&file, 3).starts_with(" 12 notes  "));
+        assert!(long_details(&file, 3).ends_with("     20 B"));
+
+        let encrypted = NoteFileInfo {
+            note_count: None,
+            encrypted: true,
+            ..file
+        };
+        assert!(long_details(&encrypted, 3).starts_with("encrypted  "));
+    }
+
+    #[test]
+    fn test_note_files_sorted() -> Result<(), String> {
+        let directory = "./rn-tests-data/listingtest/";
+        let _ = fs::remove_dir_all(directory);
+        fs::create_dir_all(directory).unwrap();
+        fs::write(format!("{}b", directory), "one\ntwo\nthree\n").unwrap();
+        fs::write(format!("{}a", directory), "one\n").unwrap();
+        fs::write(format!("{}a~", directory), "one\n").unwrap();
+        fs::create_dir_all(format!("{}journal/.git", directory)).unwrap();
+        fs::create_dir_all(format!("{}archive", directory)).unwrap();
+        fs::write(format!("{}archive/old", directory), "one\n").unwrap();
+        fs::write(format!("{}journal/2026-10-18", directory), "one\ntwo\n").unwrap();
+
+        let names = |files: Vec<NoteFileInfo>| -> Vec<String> {
+            files.into_iter().map(|file| file.name).collect()
+        };
+
+        assert_eq!(
+            names(note_files(directory, FileSort::Name)?),
+            vec!["a", "b", "journal/2026-10-18"]
+        );
+        assert_eq!(
+            names(note_files(directory, FileSort::Count)?),
+            vec!["b", "journal/2026-10-18", "a"]
+        );
+        assert_eq!(
+            note_files(directory, FileSort::Count)?[0].note_count,
+            Some(3)
+        );
+        Ok(())
+    }
+}
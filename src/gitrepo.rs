@@ -0,0 +1,113 @@
+use std::path::Path;
+use std::process::Command;
+
+use crate::notebooklock::NotebookLock;
+
+/// Version history is kept when the notes directory holds a git repository of its own,
+/// e.g. after `git -C ~/.rn init`.
+pub fn is_enabled(working_directory: &str) -> bool {
+    Path::new(working_directory).join(".git").exists()
+}
+
+/// Commits every change in the notes directory, doing nothing if there are none.
+pub fn commit_all(working_directory: &str, message: &str) -> Result<(), String> {
+    let lock_file_exclude = format!(":(exclude){}", NotebookLock::file_name());
+    git(
+        working_directory,
+        &["add", "--all", "--", ".", &lock_file_exclude],
+    )?;
+
+    if git(working_directory, &["diff", "--cached", "--quiet"]).is_ok() {
+        return Ok(());
+    }
+
+    git(
+        working_directory,
+        &["commit", "--quiet", "--message", message],
+    )?;
+    Ok(())
+}
+
+/// One line per commit, newest first, optionally only the commits that touched `file`.
+pub fn log(working_directory: &str, file: Option<&str>) -> Result<String, String> {
+    let mut args = vec!["log", "--date=short", "--format=%h %ad %s"];
+
+    if let Some(file) = file {
+        args.push("--");
+        args.push(file);
+    }
+
+    git(working_directory, &args)
+}
+
+/// Brings a note file back to how it was at `revision`.
+pub fn restore(working_directory: &str, file: &str, revision: &str) -> Result<(), String> {
+    git(working_directory, &["checkout", revision, "--", file])?;
+    Ok(())
+}
+
+/// Contents of a note file as of `revision`, which may also be a date such as `2026-10-01`
+/// or `2 weeks ago`. `None` when the file did not exist at that point.
+pub fn file_at(
+    working_directory: &str,
+    file: &str,
+    revision: &str,
+) -> Result<Option<String>, String> {
+    let object = format!(
+        "{}:{}",
+        resolve_revision(working_directory, revision)?,
+        file
+    );
+
+    if git(working_directory, &["cat-file", "-e", &object]).is_err() {
+        return Ok(None);
+    }
+
+    git(working_directory, &["show", &object]).map(Some)
+}
+
+/// Finds the commit `revision` names, or failing that the last commit made before it.
+fn resolve_revision(working_directory: &str, revision: &str) -> Result<String, String> {
+    let commit = format!("{}^{{commit}}", revision);
+
+    if let Ok(hash) = git(
+        working_directory,
+        &["rev-parse", "--verify", "--quiet", &commit],
+    ) {
+        return Ok(hash.trim().to_string());
+    }
+
+    let before = format!("--before={}", revision);
+    let hash = git(working_directory, &["rev-list", "-1", &before, "HEAD"])?;
+
+    if hash.trim().is_empty() {
+        Err(format!(
+            "No revision of the notes at or before {}.",
+            revision
+        ))
+    } else {
+        Ok(hash.trim().to_string())
+    }
+}
+
+fn git(working_directory: &str, args: &[&str]) -> Result<String, String> {
+    let output = match Command::new("git")
+        .arg("-C")
+        .arg(working_directory)
+        .args(args)
+        .output()
+    {
+        Ok(o) => o,
+        Err(e) => return Err(format!("Unable to run git: {}", e)),
+    };
+
+    if output.status.success() {
+        Ok(String::from_utf8_lossy(&output.stdout).into_owned())
+    } else {
+        Err(format!(
+            "git {} failed: {}",
+            args[0],
+            String::from_utf8_lossy(&output.stderr).trim()
+        ))
+    }
+}
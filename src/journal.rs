@@ -0,0 +1,160 @@
+//! Journal notes are kept in one note file a day, `journal/<date>`, so that `rn journal
+//! "text"` always adds to the file of today.
+
+use chrono::{Days, NaiveDate};
+
+use crate::duedate::DATE_FORMAT;
+
+pub const JOURNAL_DIRECTORY: &str = "journal";
+
+/// Days `rn journal l last-week` covers, today included.
+const WEEK_DAYS: u64 = 7;
+
+/// Note file of the journal for `date`.
+pub fn journal_file(date: NaiveDate) -> String {
+    format!("{}/{}", JOURNAL_DIRECTORY, date.format(DATE_FORMAT))
+}
+
+/// The day a file in `journal/` is for, `None` for files not named like a day.
+pub fn journal_date(name: &str) -> Option<NaiveDate> {
+    NaiveDate::parse_from_str(name, DATE_FORMAT)
+        .ok()
+        .filter(|date| date.format(DATE_FORMAT).to_string() == name)
+}
+
+/// A day of the journal, given as `2026-10-18`, `today` or `yesterday`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalDay {
+    Today,
+    Yesterday,
+    Date(NaiveDate),
+}
+
+impl JournalDay {
+    pub fn parse(value: &str) -> Result<JournalDay, String> {
+        match value.trim().to_lowercase().as_str() {
+            "today" => Ok(JournalDay::Today),
+            "yesterday" => Ok(JournalDay::Yesterday),
+            value => match NaiveDate::parse_from_str(value, DATE_FORMAT) {
+                Ok(date) => Ok(JournalDay::Date(date)),
+                Err(_) => Err(format!(
+                    "Unable to read day '{}', expected e.g. 2026-10-18, today or yesterday",
+                    value
+                )),
+            },
+        }
+    }
+
+    pub fn resolve(&self, today: NaiveDate) -> NaiveDate {
+        match *self {
+            JournalDay::Today => today,
+            JournalDay::Yesterday => today.pred_opt().unwrap_or(today),
+            JournalDay::Date(date) => date,
+        }
+    }
+}
+
+/// The days of the journal a command is about.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum JournalPeriod {
+    Day(JournalDay),
+    /// The last seven days, today included.
+    LastWeek,
+    /// From the first day to the second, both included.
+    Range(JournalDay, JournalDay),
+}
+
+impl JournalPeriod {
+    /// Reads a single day or `last-week`.
+    pub fn parse(value: &str) -> Result<JournalPeriod, String> {
+        match value.trim().to_lowercase().as_str() {
+            "last-week" => Ok(JournalPeriod::LastWeek),
+            value => Ok(JournalPeriod::Day(JournalDay::parse(value)?)),
+        }
+    }
+
+    /// The first and last day of the period, or an error when a range goes backwards.
+    pub fn bounds(&self, today: NaiveDate) -> Result<(NaiveDate, NaiveDate), String> {
+        let (first, last) = match self {
+            JournalPeriod::Day(day) => (day.resolve(today), day.resolve(today)),
+            JournalPeriod::LastWeek => (
+                today
+                    .checked_sub_days(Days::new(WEEK_DAYS - 1))
+                    .unwrap_or(today),
+                today,
+            ),
+            JournalPeriod::Range(first, last) => (first.resolve(today), last.resolve(today)),
+        };
+
+        if first > last {
+            return Err(format!(
+                "Range {} to {} goes backwards.",
+                first.format(DATE_FORMAT),
+                last.format(DATE_FORMAT)
+            ));
+        }
+
+        Ok((first, last))
+    }
+}
+
+#[allow(unused_variables)]
+mod journal_tests {
+    #[allow(unused_imports)]
+    use crate::journal::{journal_date, journal_file, JournalDay, JournalPeriod};
+    #[allow(unused_imports)]
+    use chrono::NaiveDate;
+
+    #[allow(dead_code)]
+    fn date(year: i32, month: u32, day: u32) -> NaiveDate {
+        NaiveDate::from_ymd_opt(year, month, day).unwrap()
+    }
+
+    #[test]
+    fn test_journal_file() {
+        assert_eq!(journal_file(date(2026, 10, 8)), "journal/2026-10-08");
+        assert_eq!(journal_date("2026-10-08"), Some(date(2026, 10, 8)));
+        assert_eq!(journal_date("2026-10-8"), None);
+        assert_eq!(journal_date("notes"), None);
+    }
+
+    #[test]
+    fn test_parse_days() {
+        assert_eq!(JournalDay::parse("today"), Ok(JournalDay::Today));
+        assert_eq!(JournalDay::parse("Yesterday"), Ok(JournalDay::Yesterday));
+        assert_eq!(
+            JournalDay::parse("2026-10-18"),
+            Ok(JournalDay::Date(date(2026, 10, 18)))
+        );
+        assert_eq!(
+            JournalPeriod::parse("last-week"),
+            Ok(JournalPeriod::LastWeek)
+        );
+
+        for value in ["", "tomorrow", "2026-13-01", "friday"] {
+            assert!(JournalPeriod::parse(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_period_bounds() {
+        let today = date(2026, 10, 19);
+
+        assert_eq!(
+            JournalPeriod::Day(JournalDay::Yesterday).bounds(today),
+            Ok((date(2026, 10, 18), date(2026, 10, 18)))
+        );
+        assert_eq!(
+            JournalPeriod::LastWeek.bounds(today),
+            Ok((date(2026, 10, 13), today))
+        );
+
+        let range = JournalPeriod::Range(JournalDay::Date(date(1, 1, 1)), JournalDay::Today);
+        assert_eq!(range.bounds(today), Ok((date(1, 1, 1), today)));
+        assert!(
+            JournalPeriod::Range(JournalDay::Today, JournalDay::Yesterday)
+                .bounds(today)
+                .is_err()
+        );
+    }
+}
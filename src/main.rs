@@ -1,28 +1,84 @@
+mod clipboard;
+mod config;
+mod duedate;
+mod editor;
+mod encryption;
+mod filelisting;
+mod gitrepo;
+mod journal;
+mod masking;
+mod notebooklock;
+mod notediff;
+mod notefile;
+mod pager;
 mod parsearguments;
+mod revisions;
 mod rntool;
+mod selection;
+mod shellintegration;
+mod terminal;
 
-use parsearguments::{rn_get_command_type, CommandType};
+use chrono::Local;
+use config::Config;
+use parsearguments::{
+    rn_get_command_type, rn_get_note_options, rn_get_output_options, CommandType,
+};
 use rntool::RnTool;
 use std::env::{self};
+use std::process;
 
 fn main() {
-    let args: Vec<String> = env::args().collect();
-    let command_type = rn_get_command_type(args);
     let homedir = dirs::home_dir().unwrap();
 
     // Current working directory is always /home/<user>/.rn/
     let working_directory = format!("{}/.rn/", homedir.as_os_str().to_str().unwrap());
 
+    let config = match Config::load(&working_directory) {
+        Ok(config) => config,
+        Err(x) => panic!("Invalid configuration: {}", x),
+    };
+
+    let mut args = config.expand_aliases(env::args().collect());
+    let output_options = match rn_get_output_options(&mut args) {
+        Ok(output_options) => output_options,
+        Err(x) => panic!("Illegal Argument(s): {}", x),
+    };
+    let note_options = match rn_get_note_options(&mut args, Local::now().date_naive()) {
+        Ok(note_options) => note_options,
+        Err(x) => panic!("Illegal Argument(s): {}", x),
+    };
+
+    let command_type = rn_get_command_type(args.clone());
+    let command_type = config.apply_default_file(&args, command_type, &working_directory);
+
+    if let Some(option) = note_options
+        .given()
+        .into_iter()
+        .find(|option| !command_type.takes_option(option))
+    {
+        if !matches!(command_type, CommandType::Error(_)) {
+            panic!(
+                "Illegal Argument(s): {} can not be used with this command.",
+                option
+            )
+        }
+    }
+
     match command_type {
         CommandType::Error(x) => {
             panic!("Illegal Argument(s): {}", x)
         }
         ctype => {
-            let rntool = RnTool::new(working_directory, ctype);
+            let rntool = RnTool::new(working_directory, ctype)
+                .with_output_options(output_options)
+                .with_note_options(note_options)
+                .with_config(config);
 
             if let Err(x) = rntool.execute_command() {
                 panic!("Command failed: {}", x)
             }
+
+            process::exit(rntool.exit_status());
         }
     }
 }
@@ -0,0 +1,86 @@
+use std::fs::{File, OpenOptions, TryLockError};
+use std::thread;
+use std::time::{Duration, Instant};
+
+const LOCK_FILE_NAME: &str = ".rn.lock";
+const LOCK_TIMEOUT: Duration = Duration::from_secs(5);
+const LOCK_RETRY_INTERVAL: Duration = Duration::from_millis(50);
+
+/// Advisory lock over the whole notebook. Held while a command modifies note files so
+/// that concurrent rn invocations can not interleave their writes. Released on drop.
+pub struct NotebookLock {
+    _file: File,
+}
+
+impl NotebookLock {
+    pub fn acquire(working_directory: &str) -> Result<NotebookLock, String> {
+        NotebookLock::acquire_with_timeout(working_directory, LOCK_TIMEOUT)
+    }
+
+    pub fn acquire_with_timeout(
+        working_directory: &str,
+        timeout: Duration,
+    ) -> Result<NotebookLock, String> {
+        let file = match OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(format!("{}{}", working_directory, LOCK_FILE_NAME))
+        {
+            Ok(f) => f,
+            Err(e) => return Err(format!("Unable to open notebook lock: {}", e)),
+        };
+
+        let started = Instant::now();
+
+        loop {
+            match file.try_lock() {
+                Ok(()) => return Ok(NotebookLock { _file: file }),
+                Err(TryLockError::WouldBlock) => {
+                    if started.elapsed() >= timeout {
+                        return Err(format!(
+                            "Notebook busy: another rn command is modifying {} (gave up after {} ms).",
+                            working_directory,
+                            timeout.as_millis()
+                        ));
+                    }
+                    thread::sleep(LOCK_RETRY_INTERVAL);
+                }
+                Err(TryLockError::Error(e)) => {
+                    return Err(format!("Unable to lock notebook: {}", e));
+                }
+            }
+        }
+    }
+
+    /// Name of the lock file inside the working directory, so listings can skip it.
+    pub fn file_name() -> &'static str {
+        LOCK_FILE_NAME
+    }
+}
+
+#[allow(unused_variables)]
+mod notebooklock_tests {
+    #[allow(unused_imports)]
+    use crate::notebooklock::NotebookLock;
+    #[allow(unused_imports)]
+    use std::{fs, time::Duration};
+
+    #[test]
+    fn test_second_lock_times_out_as_busy() -> Result<(), String> {
+        let working_directory = "./rn-tests-data/locktest/";
+        fs::create_dir_all(working_directory).unwrap();
+
+        let first = NotebookLock::acquire(working_directory)?;
+
+        match NotebookLock::acquire_with_timeout(working_directory, Duration::from_millis(100)) {
+            Err(e) if e.starts_with("Notebook busy") => {}
+            Err(e) => return Err(format!("Unexpected lock error: {}", e)),
+            Ok(_) => return Err("Lock was acquired twice.".to_string()),
+        }
+
+        drop(first);
+
+        NotebookLock::acquire(working_directory).map(|_| ())
+    }
+}
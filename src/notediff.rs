@@ -0,0 +1,133 @@
+use crate::notefile::Note;
+
+/// A difference between two versions of a note file. Indices point into the version the
+/// note is found in.
+#[derive(Debug, PartialEq, Eq)]
+pub enum NoteChange<'a> {
+    Added(usize, &'a Note),
+    Removed(usize, &'a Note),
+    Changed(usize, &'a Note, usize, &'a Note),
+}
+
+/// Compares two versions of a note file note by note. Notes kept as they were are matched
+/// up first; a removed note followed by an added one in the same place counts as changed.
+pub fn diff_notes<'a>(old: &'a [Note], new: &'a [Note]) -> Vec<NoteChange<'a>> {
+    // lengths[i][j] is the length of the longest common sequence of old[i..] and new[j..]
+    let mut lengths = vec![vec![0usize; new.len() + 1]; old.len() + 1];
+
+    for i in (0..old.len()).rev() {
+        for j in (0..new.len()).rev() {
+            lengths[i][j] = if old[i] == new[j] {
+                lengths[i + 1][j + 1] + 1
+            } else {
+                lengths[i + 1][j].max(lengths[i][j + 1])
+            };
+        }
+    }
+
+    let mut changes = Vec::new();
+    let mut removed = Vec::new();
+    let mut added = Vec::new();
+    let (mut i, mut j) = (0, 0);
+
+    while i < old.len() || j < new.len() {
+        if i < old.len() && j < new.len() && old[i] == new[j] {
+            pair_changes(&mut changes, &mut removed, &mut added, old, new);
+            i += 1;
+            j += 1;
+        } else if j < new.len() && (i == old.len() || lengths[i][j + 1] >= lengths[i + 1][j]) {
+            added.push(j);
+            j += 1;
+        } else {
+            removed.push(i);
+            i += 1;
+        }
+    }
+
+    pair_changes(&mut changes, &mut removed, &mut added, old, new);
+    changes
+}
+
+/// Turns the notes removed and added between two unchanged notes into changes.
+fn pair_changes<'a>(
+    changes: &mut Vec<NoteChange<'a>>,
+    removed: &mut Vec<usize>,
+    added: &mut Vec<usize>,
+    old: &'a [Note],
+    new: &'a [Note],
+) {
+    let paired = removed.len().min(added.len());
+
+    for k in 0..paired {
+        changes.push(NoteChange::Changed(
+            removed[k],
+            &old[removed[k]],
+            added[k],
+            &new[added[k]],
+        ));
+    }
+    for &i in &removed[paired..] {
+        changes.push(NoteChange::Removed(i, &old[i]));
+    }
+    for &j in &added[paired..] {
+        changes.push(NoteChange::Added(j, &new[j]));
+    }
+
+    removed.clear();
+    added.clear();
+}
+
+#[allow(unused_variables)]
+mod notediff_tests {
+    #[allow(unused_imports)]
+    use crate::notediff::{diff_notes, NoteChange};
+    #[allow(unused_imports)]
+    use crate::notefile::Note;
+
+    #[allow(dead_code)]
+    fn notes(texts: &[&str]) -> Vec<Note> {
+        texts.iter().map(|text| Note::new(text)).collect()
+    }
+
+    #[test]
+    fn test_identical_files() {
+        let old = notes(&["a", "b"]);
+        assert_eq!(diff_notes(&old, &old.clone()), vec![]);
+    }
+
+    #[test]
+    fn test_added_and_removed_notes() {
+        let old = notes(&["a", "b", "c"]);
+        let new = notes(&["b", "c", "d"]);
+        assert_eq!(
+            diff_notes(&old, &new),
+            vec![
+                NoteChange::Removed(0, &old[0]),
+                NoteChange::Added(2, &new[2]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_changed_note() {
+        let old = notes(&["a", "b", "c"]);
+        let new = notes(&["a", "B", "c", "d"]);
+        assert_eq!(
+            diff_notes(&old, &new),
+            vec![
+                NoteChange::Changed(1, &old[1], 1, &new[1]),
+                NoteChange::Added(3, &new[3]),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_note_moved_after_removal() {
+        let old = notes(&["a", "b", "c", "d"]);
+        let new = notes(&["a", "c", "d"]);
+        assert_eq!(
+            diff_notes(&old, &new),
+            vec![NoteChange::Removed(1, &old[1])]
+        );
+    }
+}
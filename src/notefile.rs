@@ -0,0 +1,710 @@
+//! Note files hold one note per line. A note that needs more than its plain text, such as
+//! a note spanning several lines, starts with a `%{...}` header listing its attributes:
+//!
+//! ```text
+//! eval $(ssh-agent)
+//! %{multiline} first line\nsecond line
+//! ```
+//!
+//! The text of a `multiline` note has its line breaks and backslashes escaped. Plain lines
+//! are read as they are, so files written by hand or by older versions stay valid. A line
+//! that starts with `%{` but has no header of known attributes, e.g. a curl
+//! `%{http_code}` line, is plain text as well, and such text is written after an empty
+//! `%{}` header.
+//!
+//! Attributes with values are written as `key=value`, with `\`, `,` and `}` in the value
+//! escaped by a backslash:
+//!
+//! ```text
+//! %{exit=0,cwd=/home/user/project} cargo build --release
+//! ```
+//!
+//! Points in time are stored in UTC, e.g. `replaced=2026-10-19T08:30:00Z`.
+//!
+//! Due dates are days without a time, e.g. `due=2026-11-01`. To-do notes are marked `todo`
+//! while open and `done` once done:
+//!
+//! ```text
+//! %{todo} renew certificates
+//! %{done,completed=2026-10-19T08:30:00Z} rotate ssh keys
+//! ```
+//!
+//! Pinned notes are marked `pinned`, and a priority is one of `priority=high`, `med` or
+//! `low`. Notes that have a header keep the time they were added and last edited in it as
+//! `created` and `modified`. Plain notes are not given a header only for these times.
+
+use std::cmp::Reverse;
+
+use chrono::{DateTime, NaiveDate, NaiveDateTime, Utc};
+
+use crate::duedate::DATE_FORMAT;
+
+const HEADER_START: &str = "%{";
+const HEADER_END: char = '}';
+const ATTRIBUTE_SEPARATOR: char = ',';
+
+const MULTILINE: &str = "multiline";
+const EXIT_STATUS: &str = "exit";
+const WORKING_DIRECTORY: &str = "cwd";
+const HISTORY: &str = "history";
+const REPLACED: &str = "replaced";
+const SECRET: &str = "secret";
+const TODO: &str = "todo";
+const DONE: &str = "done";
+const COMPLETED: &str = "completed";
+const DUE: &str = "due";
+const PINNED: &str = "pinned";
+const PRIORITY: &str = "priority";
+const CREATED: &str = "created";
+const MODIFIED: &str = "modified";
+
+const ATTRIBUTES: [&str; 14] = [
+    MULTILINE,
+    EXIT_STATUS,
+    WORKING_DIRECTORY,
+    HISTORY,
+    REPLACED,
+    SECRET,
+    TODO,
+    DONE,
+    COMPLETED,
+    DUE,
+    PINNED,
+    PRIORITY,
+    CREATED,
+    MODIFIED,
+];
+
+const TIMESTAMP_FORMAT: &str = "%Y-%m-%dT%H:%M:%SZ";
+
+/// A `key` or `key=value` entry of a note header.
+type Attribute = (String, Option<String>);
+
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct Note {
+    pub text: String,
+    /// Exit status of a note captured from the shell history.
+    pub exit_status: Option<i32>,
+    /// Directory a note captured from the shell history was run in.
+    pub cwd: Option<String>,
+    /// Links a note to its earlier bodies kept in the note history.
+    pub history: Option<u32>,
+    /// Time an earlier body in the note history was replaced by a newer one.
+    pub replaced: Option<DateTime<Utc>>,
+    /// Secret notes are masked in listings.
+    pub secret: bool,
+    /// Whether a to-do note is done, `None` for notes that are not to-do items.
+    pub done: Option<bool>,
+    /// Time a to-do note was marked done.
+    pub completed: Option<DateTime<Utc>>,
+    /// Day the note is due.
+    pub due: Option<NaiveDate>,
+    /// Pinned notes are listed first.
+    pub pinned: bool,
+    pub priority: Option<Priority>,
+    /// Time the note was added, for notes added since these times are kept.
+    pub created: Option<DateTime<Utc>>,
+    /// Time the text of the note was last edited.
+    pub modified: Option<DateTime<Utc>>,
+}
+
+/// Notes with a higher priority are listed before the rest, and low priority notes after.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum Priority {
+    High,
+    Medium,
+    Low,
+}
+
+impl Priority {
+    pub fn parse(value: &str) -> Result<Priority, String> {
+        match value {
+            "high" => Ok(Priority::High),
+            "med" | "medium" => Ok(Priority::Medium),
+            "low" => Ok(Priority::Low),
+            _ => Err(format!(
+                "Unknown priority: {}, expected high, med or low",
+                value
+            )),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Priority::High => "high",
+            Priority::Medium => "med",
+            Priority::Low => "low",
+        }
+    }
+}
+
+impl Note {
+    pub fn new(text: &str) -> Note {
+        Note {
+            text: text.replace("\r\n", "\n"),
+            ..Default::default()
+        }
+    }
+
+    /// Replaces the text of the note, keeping its attributes.
+    pub fn set_text(&mut self, text: &str) {
+        self.text = text.replace("\r\n", "\n");
+    }
+
+    /// Parses a single line of a note file.
+    pub fn parse(line: &str) -> Result<Note, String> {
+        let (attributes, text) = match line.strip_prefix(HEADER_START).map(parse_header) {
+            Some(Ok((attributes, text)))
+                if attributes
+                    .iter()
+                    .all(|(key, _)| ATTRIBUTES.contains(&key.as_str())) =>
+            {
+                (attributes, text)
+            }
+            // Text that only looks like a header, from before headers or written by hand
+            _ => return Ok(Note::new(line)),
+        };
+
+        let mut multiline = false;
+        let mut note = Note::default();
+
+        for (key, value) in attributes {
+            match (key.as_str(), value) {
+                (MULTILINE, None) => multiline = true,
+                (EXIT_STATUS, Some(value)) => match value.parse::<i32>() {
+                    Ok(status) => note.exit_status = Some(status),
+                    Err(_) => return Err(format!("invalid exit status '{}'", value)),
+                },
+                (WORKING_DIRECTORY, Some(value)) => note.cwd = Some(value),
+                (HISTORY, Some(value)) => match value.parse::<u32>() {
+                    Ok(id) => note.history = Some(id),
+                    Err(_) => return Err(format!("invalid history id '{}'", value)),
+                },
+                (REPLACED, Some(value)) => note.replaced = Some(parse_timestamp(&value)?),
+                (SECRET, None) => note.secret = true,
+                (TODO, None) => note.done = Some(false),
+                (DONE, None) => note.done = Some(true),
+                (COMPLETED, Some(value)) => note.completed = Some(parse_timestamp(&value)?),
+                (DUE, Some(value)) => match NaiveDate::parse_from_str(&value, DATE_FORMAT) {
+                    Ok(date) => note.due = Some(date),
+                    Err(_) => return Err(format!("invalid due date '{}'", value)),
+                },
+                (PINNED, None) => note.pinned = true,
+                (PRIORITY, Some(value)) => match Priority::parse(&value) {
+                    Ok(priority) => note.priority = Some(priority),
+                    Err(_) => return Err(format!("invalid priority '{}'", value)),
+                },
+                (CREATED, Some(value)) => note.created = Some(parse_timestamp(&value)?),
+                (MODIFIED, Some(value)) => note.modified = Some(parse_timestamp(&value)?),
+                (_, _) => return Err(format!("unknown note attribute '{}'", key)),
+            }
+        }
+
+        note.text = if multiline {
+            unescape_text(text)?
+        } else {
+            text.to_string()
+        };
+
+        Ok(note)
+    }
+
+    /// Formats the note as a single line of a note file.
+    pub fn to_line(&self) -> String {
+        let mut attributes: Vec<(&str, Option<String>)> = Vec::new();
+
+        let multiline = self.text.contains('\n');
+        if multiline {
+            attributes.push((MULTILINE, None));
+        }
+        if let Some(status) = self.exit_status {
+            attributes.push((EXIT_STATUS, Some(status.to_string())));
+        }
+        if let Some(cwd) = &self.cwd {
+            attributes.push((WORKING_DIRECTORY, Some(cwd.clone())));
+        }
+        if let Some(id) = self.history {
+            attributes.push((HISTORY, Some(id.to_string())));
+        }
+        if let Some(replaced) = self.replaced {
+            attributes.push((
+                REPLACED,
+                Some(replaced.format(TIMESTAMP_FORMAT).to_string()),
+            ));
+        }
+        if self.secret {
+            attributes.push((SECRET, None));
+        }
+        match self.done {
+            Some(false) => attributes.push((TODO, None)),
+            Some(true) => attributes.push((DONE, None)),
+            None => {}
+        }
+        if let Some(completed) = self.completed {
+            attributes.push((
+                COMPLETED,
+                Some(completed.format(TIMESTAMP_FORMAT).to_string()),
+            ));
+        }
+        if let Some(due) = self.due {
+            attributes.push((DUE, Some(due.format(DATE_FORMAT).to_string())));
+        }
+        if self.pinned {
+            attributes.push((PINNED, None));
+        }
+        if let Some(priority) = self.priority {
+            attributes.push((PRIORITY, Some(priority.as_str().to_string())));
+        }
+        // Plain notes stay plain lines rather than carry a header only for their times
+        let has_header = !attributes.is_empty();
+
+        if let Some(created) = self.created.filter(|_| has_header) {
+            attributes.push((CREATED, Some(created.format(TIMESTAMP_FORMAT).to_string())));
+        }
+        if let Some(modified) = self.modified.filter(|_| has_header) {
+            attributes.push((
+                MODIFIED,
+                Some(modified.format(TIMESTAMP_FORMAT).to_string()),
+            ));
+        }
+
+        let text = if multiline {
+            escape_text(&self.text)
+        } else {
+            self.text.clone()
+        };
+
+        if attributes.is_empty() && !text.starts_with(HEADER_START) {
+            return text;
+        }
+
+        let header: Vec<String> = attributes
+            .into_iter()
+            .map(|(key, value)| match value {
+                Some(value) => format!("{}={}", key, escape_value(&value)),
+                None => key.to_string(),
+            })
+            .collect();
+
+        format!(
+            "{}{}{} {}",
+            HEADER_START,
+            header.join(&ATTRIBUTE_SEPARATOR.to_string()),
+            HEADER_END,
+            text
+        )
+    }
+}
+
+/// How `rn <file> sort` orders the notes of a file.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub enum NoteSort {
+    #[default]
+    Text,
+    /// Oldest first, with notes that have no creation time, such as plain notes, leading
+    /// in their current order.
+    Created,
+    /// Most recently edited or added first.
+    Modified,
+}
+
+impl NoteSort {
+    pub fn parse(value: &str) -> Result<NoteSort, String> {
+        match value {
+            "text" => Ok(NoteSort::Text),
+            "created" => Ok(NoteSort::Created),
+            "modified" => Ok(NoteSort::Modified),
+            _ => Err(format!(
+                "Unknown sort order: {}, expected text, created or modified",
+                value
+            )),
+        }
+    }
+}
+
+/// Orders notes as asked, keeping the order of notes that compare equal.
+pub fn sort_notes(notes: &mut [Note], sort: NoteSort) {
+    match sort {
+        NoteSort::Text => notes.sort_by_key(|note| note.text.to_lowercase()),
+        NoteSort::Created => notes.sort_by_key(|note| note.created),
+        NoteSort::Modified => notes.sort_by_key(|note| Reverse(note.modified.or(note.created))),
+    }
+}
+
+/// Parses the contents of a note file. Errors name the offending line (1-based).
+pub fn parse_notes(content: &str) -> Result<Vec<Note>, String> {
+    let mut notes = Vec::new();
+
+    for (index, line) in content.lines().enumerate() {
+        match Note::parse(line) {
+            Ok(note) => notes.push(note),
+            Err(e) => return Err(format!("line {}: {}", index + 1, e)),
+        }
+    }
+
+    Ok(notes)
+}
+
+/// Formats notes as the contents of a note file.
+pub fn format_notes(notes: &[Note]) -> String {
+    let mut content = String::new();
+
+    for note in notes {
+        content.push_str(&note.to_line());
+        content.push('\n');
+    }
+
+    content
+}
+
+/// Checks that file contents are in the note format, with no empty lines in between notes.
+/// Errors name the first offending line (1-based).
+pub fn check_note_format(content: &str) -> Result<(), String> {
+    parse_notes(content)?;
+
+    for (index, line) in content.lines().enumerate() {
+        if line.trim().is_empty() {
+            return Err(format!("line {} is empty", index + 1));
+        }
+    }
+
+    Ok(())
+}
+
+/// Splits `key,key=value} text` into its attributes and the text after the header.
+fn parse_header(line: &str) -> Result<(Vec<Attribute>, &str), String> {
+    let mut attributes = Vec::new();
+    let mut token = String::new();
+    let mut chars = line.char_indices();
+
+    loop {
+        match chars.next() {
+            Some((_, '\\')) => match chars.next() {
+                Some((_, c)) => token.push(c),
+                None => return Err("unterminated note header".to_string()),
+            },
+            Some((_, ATTRIBUTE_SEPARATOR)) => {
+                attributes.push(parse_attribute(&token)?);
+                token.clear();
+            }
+            Some((i, HEADER_END)) => {
+                if !token.is_empty() || !attributes.is_empty() {
+                    attributes.push(parse_attribute(&token)?);
+                }
+
+                let rest = &line[i + 1..];
+                let text = match rest.strip_prefix(' ') {
+                    Some(text) => text,
+                    None if rest.is_empty() => rest,
+                    None => return Err("missing space after note header".to_string()),
+                };
+
+                return Ok((attributes, text));
+            }
+            Some((_, c)) => token.push(c),
+            None => return Err("unterminated note header".to_string()),
+        }
+    }
+}
+
+fn parse_attribute(token: &str) -> Result<Attribute, String> {
+    let (key, value) = match token.split_once('=') {
+        Some((key, value)) => (key.trim(), Some(value.to_string())),
+        None => (token.trim(), None),
+    };
+
+    if key.is_empty() {
+        return Err("empty note attribute".to_string());
+    }
+
+    Ok((key.to_string(), value))
+}
+
+fn parse_timestamp(value: &str) -> Result<DateTime<Utc>, String> {
+    match NaiveDateTime::parse_from_str(value, TIMESTAMP_FORMAT) {
+        Ok(timestamp) => Ok(timestamp.and_utc()),
+        Err(_) => Err(format!("invalid timestamp '{}'", value)),
+    }
+}
+
+fn escape_value(value: &str) -> String {
+    let mut escaped = String::new();
+
+    for c in value.chars() {
+        if c == '\\' || c == ATTRIBUTE_SEPARATOR || c == HEADER_END {
+            escaped.push('\\');
+        }
+        escaped.push(c);
+    }
+
+    escaped
+}
+
+fn escape_text(text: &str) -> String {
+    text.replace('\\', "\\\\").replace('\n', "\\n")
+}
+
+fn unescape_text(text: &str) -> Result<String, String> {
+    let mut unescaped = String::new();
+    let mut chars = text.chars();
+
+    while let Some(c) = chars.next() {
+        if c != '\\' {
+            unescaped.push(c);
+            continue;
+        }
+
+        match chars.next() {
+            Some('n') => unescaped.push('\n'),
+            Some('\\') => unescaped.push('\\'),
+            Some(other) => return Err(format!("unknown escape '\\{}' in note text", other)),
+            None => return Err("note text ends in a lone backslash".to_string()),
+        }
+    }
+
+    Ok(unescaped)
+}
+
+#[allow(unused_variables)]
+mod notefile_tests {
+    #[allow(unused_imports)]
+    use crate::notefile::{
+        check_note_format, format_notes, parse_notes, sort_notes, Note, NoteSort, Priority,
+    };
+    #[allow(unused_imports)]
+    use chrono::{NaiveDate, TimeZone, Utc};
+
+    #[test]
+    fn test_valid_note_file() {
+        assert_eq!(check_note_format("first note\nsecond note\n"), Ok(()));
+        assert_eq!(check_note_format(""), Ok(()));
+    }
+
+    #[test]
+    fn test_empty_line_in_note_file() {
+        assert_eq!(
+            check_note_format("first note\n\nthird note\n"),
+            Err("line 2 is empty".to_string())
+        );
+    }
+
+    #[test]
+    fn test_plain_lines_are_notes() {
+        let notes = parse_notes("eval $(ssh-agent)\nprintf 'a\\n'\n").unwrap();
+        assert_eq!(
+            notes,
+            vec![Note::new("eval $(ssh-agent)"), Note::new("printf 'a\\n'")]
+        );
+    }
+
+    #[test]
+    fn test_multiline_note_round_trip() {
+        let note = Note::new("first line\nsecond \\ line");
+        let line = note.to_line();
+        assert_eq!(line, "%{multiline} first line\\nsecond \\\\ line");
+        assert_eq!(Note::parse(&line), Ok(note));
+    }
+
+    #[test]
+    fn test_text_that_looks_like_a_header_round_trip() {
+        let note = Note::new("%{not a header}");
+        let line = note.to_line();
+        assert_eq!(line, "%{} %{not a header}");
+        assert_eq!(Note::parse(&line), Ok(note));
+    }
+
+    #[test]
+    fn test_attribute_values_round_trip() {
+        let note = Note {
+            exit_status: Some(1),
+            cwd: Some("/tmp/a,b}c".to_string()),
+            ..Note::new("make test")
+        };
+        let line = note.to_line();
+        assert_eq!(line, "%{exit=1,cwd=/tmp/a\\,b\\}c} make test");
+        assert_eq!(Note::parse(&line), Ok(note));
+        assert_eq!(
+            Note::parse("%{exit=one} make test"),
+            Err("invalid exit status 'one'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_timestamp_round_trip() {
+        let note = Note {
+            history: Some(2),
+            replaced: Some(Utc.with_ymd_and_hms(2026, 10, 19, 8, 30, 0).unwrap()),
+            ..Note::new("old body")
+        };
+        let line = note.to_line();
+        assert_eq!(line, "%{history=2,replaced=2026-10-19T08:30:00Z} old body");
+        assert_eq!(Note::parse(&line), Ok(note));
+        assert_eq!(
+            Note::parse("%{replaced=yesterday} old body"),
+            Err("invalid timestamp 'yesterday'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_todo_round_trip() {
+        let open = Note {
+            done: Some(false),
+            ..Note::new("renew certificates")
+        };
+        assert_eq!(open.to_line(), "%{todo} renew certificates");
+        assert_eq!(Note::parse(&open.to_line()), Ok(open));
+
+        let done = Note {
+            done: Some(true),
+            completed: Some(Utc.with_ymd_and_hms(2026, 10, 19, 8, 30, 0).unwrap()),
+            ..Note::new("rotate ssh keys")
+        };
+        let line = done.to_line();
+        assert_eq!(
+            line,
+            "%{done,completed=2026-10-19T08:30:00Z} rotate ssh keys"
+        );
+        assert_eq!(Note::parse(&line), Ok(done));
+    }
+
+    #[test]
+    fn test_due_date_round_trip() {
+        let note = Note {
+            due: NaiveDate::from_ymd_opt(2026, 11, 1),
+            ..Note::new("renew certificates")
+        };
+        let line = note.to_line();
+        assert_eq!(line, "%{due=2026-11-01} renew certificates");
+        assert_eq!(Note::parse(&line), Ok(note));
+        assert!(Note::parse("%{due=tomorrow} renew certificates").is_err());
+    }
+
+    #[test]
+    fn test_priority_round_trip() {
+        let note = Note {
+            pinned: true,
+            priority: Some(Priority::High),
+            ..Note::new("ssh -L 5432:localhost:5432 db")
+        };
+        let line = note.to_line();
+        assert_eq!(
+            line,
+            "%{pinned,priority=high} ssh -L 5432:localhost:5432 db"
+        );
+        assert_eq!(Note::parse(&line), Ok(note));
+        assert!(Note::parse("%{priority=urgent} note").is_err());
+    }
+
+    #[test]
+    fn test_timestamps_round_trip() {
+        let note = Note {
+            pinned: true,
+            created: Some(Utc.with_ymd_and_hms(2026, 10, 19, 8, 30, 0).unwrap()),
+            modified: Some(Utc.with_ymd_and_hms(2026, 10, 20, 9, 0, 0).unwrap()),
+            ..Note::new("ssh db")
+        };
+        let line = note.to_line();
+        assert_eq!(
+            line,
+            "%{pinned,created=2026-10-19T08:30:00Z,modified=2026-10-20T09:00:00Z} ssh db"
+        );
+        assert_eq!(Note::parse(&line), Ok(note));
+    }
+
+    #[test]
+    fn test_plain_notes_leave_out_times() {
+        let note = Note {
+            created: Some(Utc.with_ymd_and_hms(2026, 10, 19, 8, 30, 0).unwrap()),
+            ..Note::new("ssh db")
+        };
+        assert_eq!(note.to_line(), "ssh db");
+    }
+
+    #[test]
+    fn test_sort_notes() {
+        let at = |day| Some(Utc.with_ymd_and_hms(2026, 10, day, 8, 0, 0).unwrap());
+        let note = |text: &str, created, modified| Note {
+            created,
+            modified,
+            ..Note::new(text)
+        };
+        let notes = vec![
+            note("beta", at(2), None),
+            note("Alpha", at(3), None),
+            note("gamma", None, None),
+            note("delta", at(1), at(4)),
+        ];
+        let texts = |sort| -> Vec<String> {
+            let mut sorted = notes.clone();
+            sort_notes(&mut sorted, sort);
+            sorted.into_iter().map(|note| note.text).collect()
+        };
+
+        assert_eq!(
+            texts(NoteSort::Text),
+            vec!["Alpha", "beta", "delta", "gamma"]
+        );
+        assert_eq!(
+            texts(NoteSort::Created),
+            vec!["gamma", "delta", "beta", "Alpha"]
+        );
+        assert_eq!(
+            texts(NoteSort::Modified),
+            vec!["delta", "Alpha", "beta", "gamma"]
+        );
+        assert!(NoteSort::parse("size").is_err());
+    }
+
+    #[test]
+    fn test_secret_round_trip() {
+        let note = Note {
+            secret: true,
+            ..Note::new("hunter2")
+        };
+        let line = note.to_line();
+        assert_eq!(line, "%{secret} hunter2");
+        assert_eq!(Note::parse(&line), Ok(note));
+    }
+
+    #[test]
+    fn test_format_notes() {
+        let notes = vec![Note::new("one"), Note::new("two\nlines")];
+        let content = format_notes(&notes);
+        assert_eq!(content, "one\n%{multiline} two\\nlines\n");
+        assert_eq!(parse_notes(&content), Ok(notes));
+    }
+
+    #[test]
+    fn test_invalid_headers() {
+        assert_eq!(
+            parse_notes("%{multiline} first\\qsecond"),
+            Err("line 1: unknown escape '\\q' in note text".to_string())
+        );
+        assert_eq!(
+            parse_notes("%{todo=maybe} text"),
+            Err("line 1: unknown note attribute 'todo'".to_string())
+        );
+    }
+
+    #[test]
+    fn test_lines_that_only_look_like_headers() {
+        let lines = [
+            "curl -s -o /dev/null -w %{http_code} example.com",
+            "%{http_code} is what curl -w prints",
+            "%{multiline text",
+            "%{multiline}text",
+            "%{todo,bogus} text",
+        ];
+
+        for line in lines {
+            let note = Note::parse(line).unwrap();
+            assert_eq!(note, Note::new(line));
+        }
+
+        let content = "%{http_code}\n";
+        let notes = parse_notes(content).unwrap();
+        assert_eq!(notes, vec![Note::new("%{http_code}")]);
+        assert_eq!(format_notes(&notes), "%{} %{http_code}\n");
+        assert_eq!(parse_notes(&format_notes(&notes)), Ok(notes));
+    }
+}
@@ -0,0 +1,81 @@
+use std::env;
+use std::io::{ErrorKind, Write};
+use std::process::{Command, Stdio};
+
+const DEFAULT_PAGER: &str = "less -R";
+
+/// Exit statuses of `sh` when the command could not be run or was not found.
+const NOT_STARTED_STATUSES: [i32; 2] = [126, 127];
+
+/// Pager to use: the configured one, then `$PAGER`, then `less -R`. `None` when paging has
+/// been turned off with an empty setting.
+pub fn pager_command(configured: Option<&str>) -> Option<String> {
+    let command = match configured {
+        Some(command) => command.to_string(),
+        None => env::var("PAGER").unwrap_or_else(|_| DEFAULT_PAGER.to_string()),
+    };
+
+    if command.trim().is_empty() {
+        None
+    } else {
+        Some(command)
+    }
+}
+
+/// Shows output in the pager, `Ok(false)` when the pager could not be started so the output
+/// is still to be shown. Quitting the pager before reading everything is not an error.
+pub fn page(command: &str, output: &str) -> Result<bool, String> {
+    let mut child = match Command::new("sh")
+        .arg("-c")
+        .arg(command)
+        .stdin(Stdio::piped())
+        .spawn()
+    {
+        Ok(c) => c,
+        Err(_) => return Ok(false),
+    };
+
+    if let Some(mut stdin) = child.stdin.take() {
+        if let Err(e) = stdin.write_all(output.as_bytes()) {
+            if e.kind() != ErrorKind::BrokenPipe {
+                return Err(format!("Unable to write to pager '{}': {}", command, e));
+            }
+        }
+    }
+
+    match child.wait() {
+        Ok(status) if status.success() => Ok(true),
+        Ok(status) if NOT_STARTED_STATUSES.contains(&status.code().unwrap_or(0)) => Ok(false),
+        Ok(status) => Err(format!("Pager '{}' exited with error: {}", command, status)),
+        Err(e) => Err(format!("Unable to run pager '{}': {}", command, e)),
+    }
+}
+
+#[allow(unused_variables)]
+mod pager_tests {
+    #[allow(unused_imports)]
+    use crate::pager::{page, pager_command};
+    #[allow(unused_imports)]
+    use std::fs;
+
+    #[test]
+    fn test_pager_command() {
+        assert_eq!(pager_command(Some("most")), Some("most".to_string()));
+        assert_eq!(pager_command(Some("")), None);
+        assert_eq!(pager_command(Some("  ")), None);
+    }
+
+    #[test]
+    fn test_page() {
+        fs::create_dir_all("./rn-tests-data").unwrap();
+        let path = "./rn-tests-data/pagertest";
+
+        assert_eq!(
+            page(&format!("cat > {}", path), "0: first\n1: second\n"),
+            Ok(true)
+        );
+        assert_eq!(fs::read_to_string(path).unwrap(), "0: first\n1: second\n");
+        assert!(page("exit 3", "0: first\n").is_err());
+        assert_eq!(page("rn-no-such-pager", "0: first\n"), Ok(false));
+    }
+}
@@ -1,19 +1,297 @@
-#[derive(Debug, PartialEq, Eq)]
+use chrono::NaiveDate;
+
+use crate::duedate;
+use crate::filelisting::{FileSort, ListFilesOptions};
+use crate::journal::{JournalDay, JournalPeriod};
+use crate::notefile::{Note, NoteSort, Priority};
+use crate::selection::{NoteIndex, NoteSelector};
+use crate::shellintegration::SHELLS;
+use crate::terminal::ColorChoice;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum CommandType {
-    ListNoteFiles,
+    ListNoteFiles(ListFilesOptions),
+    ListDueNotes,
+    ListRecentNotes(usize),
+    ListJournal(JournalPeriod),
+    PrintJournal(JournalPeriod),
+    RemindDueNotes,
     RemoveNoteFile(String),
-    OpenNoteFileInEditor(String),
+    OpenNoteFileInEditor(String, Option<NoteIndex>),
 
-    ListNotesInFile(String),
+    ListNotesInFile(String, ListOptions),
+    ShowNote(String, NoteIndex),
     AddNoteToFile(String, String),
-    EditNoteInFile(String, usize, String),
-    RemoveNoteFromFile(String, usize),
+    AddNoteToInbox(String),
+    AddNoteToJournal(String),
+    TriageInbox,
+    AddNotesFromStdin(String, bool),
+    AddLastCommandToFile(String, bool),
+    EditNoteInFile(String, NoteIndex, String),
+    EditNoteInEditor(String, NoteIndex),
+    RemoveNoteFromFile(String, NoteSelector),
+    MoveNote(String, NoteSelector, NoteIndex),
+    SortNotes(String, NoteSort),
+    ShowNoteHistory(String, NoteIndex),
+    RevertNote(String, NoteIndex, usize),
+    MarkNoteSecret(String, NoteIndex, bool),
+    PinNote(String, NoteIndex, bool),
+    SetNoteDone(String, NoteIndex, bool),
+    CopyNoteToClipboard(String, NoteSelector),
+    EncryptNoteFile(String),
+    DecryptNoteFile(String),
+    ShowHistory(Option<String>),
+    DiffNoteFiles(String, String),
+    DiffNoteFileSince(String, String),
+    RestoreNoteFile(String, String),
     PrintHelp,
+    PrintShellIntegration(String),
     Error(String),
 }
 
+/// Number of notes `rn recent` shows unless told otherwise with `-n`.
+pub const DEFAULT_RECENT_COUNT: usize = 10;
+
+/// Which notes of a note file to list.
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq)]
+pub struct ListOptions {
+    /// Leave out to-do notes that are done.
+    pub open: bool,
+    /// Only the first notes of the file.
+    pub head: Option<usize>,
+    /// Only the last notes of the file.
+    pub tail: Option<usize>,
+}
+
+/// Attributes given to the notes being added or edited.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct NoteOptions {
+    /// `Some(None)` removes the due date, given as `--due none`.
+    pub due: Option<Option<NaiveDate>>,
+    /// `Some(None)` removes the priority, given as `--priority none`.
+    pub priority: Option<Option<Priority>>,
+}
+
+impl NoteOptions {
+    pub fn is_empty(&self) -> bool {
+        *self == NoteOptions::default()
+    }
+
+    /// The options that were given, which the command has to take.
+    pub fn given(&self) -> Vec<&'static str> {
+        [
+            ("--due", self.due.is_some()),
+            ("--priority", self.priority.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, given)| *given)
+        .map(|(option, _)| option)
+        .collect()
+    }
+
+    pub fn apply(&self, note: &mut Note) {
+        if let Some(due) = self.due {
+            note.due = due;
+        }
+        if let Some(priority) = self.priority {
+            note.priority = priority;
+        }
+    }
+}
+
+/// Takes the note options out of the arguments given at bin call
+pub fn rn_get_note_options(
+    args: &mut Vec<String>,
+    today: NaiveDate,
+) -> Result<NoteOptions, String> {
+    let due = match take_option(args, "--due")? {
+        Some(value) if value == "none" => Some(None),
+        Some(value) => Some(Some(duedate::parse_due_date(&value, today)?)),
+        None => None,
+    };
+    let priority = match take_option(args, "--priority")? {
+        Some(value) if value == "none" => Some(None),
+        Some(value) => Some(Some(Priority::parse(&value)?)),
+        None => None,
+    };
+
+    Ok(NoteOptions { due, priority })
+}
+
+/// Options about how output is shown, which can be given anywhere on the command line.
+#[derive(Debug, Default, PartialEq, Eq)]
+pub struct OutputOptions {
+    /// Show secrets in listings instead of masking them.
+    pub reveal: bool,
+    pub color: Option<ColorChoice>,
+    pub no_pager: bool,
+}
+
+/// Takes the output options out of the arguments given at bin call
+pub fn rn_get_output_options(args: &mut Vec<String>) -> Result<OutputOptions, String> {
+    let color = match take_option(args, "--color")? {
+        Some(value) => Some(ColorChoice::parse(&value)?),
+        None => None,
+    };
+
+    Ok(OutputOptions {
+        reveal: take_flag(args, "--reveal"),
+        color,
+        no_pager: take_flag(args, "--no-pager"),
+    })
+}
+
+/// Options that only some commands take.
+struct CommandOptions {
+    multiline: bool,
+    with_context: bool,
+    since: Option<String>,
+    long: bool,
+    list_options: ListOptions,
+    sort: FileSort,
+    note_sort: NoteSort,
+    match_text: Option<String>,
+    at: Option<NoteIndex>,
+    /// The options that were given, which the command has to take.
+    given: Vec<&'static str>,
+}
+
+impl CommandOptions {
+    /// Takes the options out of the arguments given at bin call
+    fn take(args: &mut Vec<String>) -> Result<CommandOptions, String> {
+        let multiline = take_flag(args, "--multiline");
+        let with_context = take_flag(args, "--context");
+        let since = take_option(args, "--since")?;
+        let long = take_flag(args, "--long");
+        let head = take_count(args, "--head")?;
+        let tail = take_count(args, "--tail")?;
+        let open = take_flag(args, "--open");
+        let sort = match take_option(args, "--sort")? {
+            Some(sort) => Some(FileSort::parse(&sort)?),
+            None => None,
+        };
+        let note_sort = match take_option(args, "--by")? {
+            Some(by) => Some(NoteSort::parse(&by)?),
+            None => None,
+        };
+        let match_text = match take_option(args, "--match")? {
+            Some(text) if text.is_empty() => return Err(String::from("Nothing to match.")),
+            match_text => match_text,
+        };
+        let at = match take_option(args, "--at")? {
+            Some(at) => Some(NoteIndex::parse(&at)?),
+            None => None,
+        };
+
+        let given = [
+            ("--multiline", multiline),
+            ("--context", with_context),
+            ("--since", since.is_some()),
+            ("--long", long),
+            ("--head", head.is_some()),
+            ("--tail", tail.is_some()),
+            ("--open", open),
+            ("--sort", sort.is_some()),
+            ("--by", note_sort.is_some()),
+            ("--match", match_text.is_some()),
+            ("--at", at.is_some()),
+        ]
+        .into_iter()
+        .filter(|(_, given)| *given)
+        .map(|(option, _)| option)
+        .collect();
+
+        Ok(CommandOptions {
+            multiline,
+            with_context,
+            since,
+            long,
+            list_options: ListOptions { open, head, tail },
+            sort: sort.unwrap_or_default(),
+            note_sort: note_sort.unwrap_or_default(),
+            match_text,
+            at,
+            given,
+        })
+    }
+}
+
+impl CommandType {
+    /// Whether the command makes use of an option such as `--open`, so that options given
+    /// to commands that would ignore them can be pointed out.
+    pub fn takes_option(&self, option: &str) -> bool {
+        match option {
+            "--multiline" => matches!(self, CommandType::AddNotesFromStdin(..)),
+            "--context" => matches!(self, CommandType::AddLastCommandToFile(..)),
+            "--since" => matches!(self, CommandType::DiffNoteFileSince(..)),
+            "--long" | "--sort" => matches!(self, CommandType::ListNoteFiles(..)),
+            "--open" | "--head" | "--tail" => matches!(self, CommandType::ListNotesInFile(..)),
+            "--by" => matches!(self, CommandType::SortNotes(..)),
+            "--at" => matches!(self, CommandType::OpenNoteFileInEditor(..)),
+            "--match" => matches!(
+                self,
+                CommandType::RemoveNoteFromFile(..)
+                    | CommandType::MoveNote(..)
+                    | CommandType::CopyNoteToClipboard(..)
+            ),
+            "--due" | "--priority" => matches!(
+                self,
+                CommandType::AddNoteToFile(..)
+                    | CommandType::AddNoteToInbox(..)
+                    | CommandType::AddNoteToJournal(..)
+                    | CommandType::AddNotesFromStdin(..)
+                    | CommandType::AddLastCommandToFile(..)
+                    | CommandType::EditNoteInFile(..)
+                    | CommandType::EditNoteInEditor(..)
+            ),
+            _ => false,
+        }
+    }
+}
+
+/// Arguments after this one are never taken for options, e.g. `rn todo a -- --open flag`.
+const OPTION_END: &str = "--";
+
 /// Takes arguments given at bin call and gets what is wanted from the tool
-pub fn rn_get_command_type(args: Vec<String>) -> CommandType {
+pub fn rn_get_command_type(mut args: Vec<String>) -> CommandType {
+    let options = match CommandOptions::take(&mut args) {
+        Ok(options) => options,
+        Err(e) => return CommandType::Error(e),
+    };
+    let given = options.given.clone();
+
+    if let Some(position) = args.iter().position(|arg| arg == OPTION_END) {
+        args.remove(position);
+    }
+
+    let command_type = command_type(args, options);
+
+    match given
+        .into_iter()
+        .find(|option| !command_type.takes_option(option))
+    {
+        Some(option) if !matches!(command_type, CommandType::Error(_)) => {
+            CommandType::Error(format!("{} can not be used with this command.", option))
+        }
+        _ => command_type,
+    }
+}
+
+/// Reads the command from the arguments left once the options are taken out.
+fn command_type(args: Vec<String>, options: CommandOptions) -> CommandType {
+    let CommandOptions {
+        multiline,
+        with_context,
+        since,
+        long,
+        list_options,
+        sort,
+        note_sort,
+        match_text,
+        at,
+        ..
+    } = options;
+
     let help_args: [String; 2] = [String::from("help"), String::from("h")];
 
     let list_args: [String; 2] = [String::from("list"), String::from("l")];
@@ -37,7 +315,7 @@ pub fn rn_get_command_type(args: Vec<String>) -> CommandType {
     }
 
     if list_args.contains(first_argument) {
-        return CommandType::ListNoteFiles;
+        return CommandType::ListNoteFiles(ListFilesOptions { long, sort });
     }
 
     if remove_args.contains(first_argument) {
@@ -53,52 +331,156 @@ pub fn rn_get_command_type(args: Vec<String>) -> CommandType {
         if args.len() < 3 {
             return CommandType::Error(String::from("Too few arguments."));
         } else {
-            let file_to_remove = args[2].clone();
-            return CommandType::OpenNoteFileInEditor(file_to_remove);
+            return CommandType::OpenNoteFileInEditor(args[2].clone(), at);
         }
     }
 
-    if args.len() < 3 {
-        return CommandType::ListNotesInFile(first_argument.to_string());
+    if first_argument == "due" {
+        return CommandType::ListDueNotes;
     }
 
-    let note_filename = first_argument;
-    let second_argument = &args[2];
+    if first_argument == "remind" {
+        return CommandType::RemindDueNotes;
+    }
 
-    if list_args.contains(second_argument) {
-        return CommandType::ListNotesInFile(note_filename.to_string());
+    if first_argument == "recent" {
+        return match args.get(2).map(String::as_str) {
+            None | Some("") => CommandType::ListRecentNotes(DEFAULT_RECENT_COUNT),
+            Some("-n") => match args.get(3).map(|count| count.parse::<usize>()) {
+                Some(Ok(count)) => CommandType::ListRecentNotes(count),
+                Some(Err(_)) => {
+                    CommandType::Error(format!("Unable to parse string to count: {}", args[3]))
+                }
+                None => CommandType::Error(String::from("Too few arguments.")),
+            },
+            Some(other) => CommandType::Error(format!("Unknown option for recent: {}", other)),
+        };
     }
 
-    if remove_args.contains(second_argument) {
+    if first_argument == "log" {
+        return CommandType::ShowHistory(args.get(2).filter(|arg| !arg.is_empty()).cloned());
+    }
+
+    if first_argument == "diff" {
+        return match (args.len(), since) {
+            (3.., Some(since)) => CommandType::DiffNoteFileSince(args[2].clone(), since),
+            (4.., None) => CommandType::DiffNoteFiles(args[2].clone(), args[3].clone()),
+            _ => CommandType::Error(String::from("Too few arguments.")),
+        };
+    }
+
+    if first_argument == "restore" {
         if args.len() < 4 {
             return CommandType::Error(String::from("Too few arguments."));
         } else {
-            let note_id_to_remove = args[3].clone().parse::<usize>();
+            return CommandType::RestoreNoteFile(args[2].clone(), args[3].clone());
+        }
+    }
 
-            match note_id_to_remove {
-                Ok(result) => {
-                    return CommandType::RemoveNoteFromFile(note_filename.to_string(), result);
+    if first_argument == "-" || first_argument == "add" {
+        let words: Vec<&str> = args[2..]
+            .iter()
+            .map(String::as_str)
+            .filter(|word| !word.is_empty())
+            .collect();
+
+        if words.is_empty() {
+            return CommandType::Error(String::from("Too few arguments."));
+        }
+
+        return CommandType::AddNoteToInbox(words.join(" "));
+    }
+
+    if first_argument == "journal" {
+        let today = CommandType::ListJournal(JournalPeriod::Day(JournalDay::Today));
+
+        return match args.get(2).map(String::as_str) {
+            None | Some("") => today,
+            Some(_) if list_args.contains(&args[2]) => match args.get(3).map(String::as_str) {
+                None | Some("") => today,
+                Some(period) => match JournalPeriod::parse(period) {
+                    Ok(period) => CommandType::ListJournal(period),
+                    Err(e) => CommandType::Error(e),
+                },
+            },
+            Some("cat") => {
+                if args.len() < 4 || args[3].is_empty() {
+                    return CommandType::Error(String::from("Too few arguments."));
                 }
-                Err(_) => {
-                    return CommandType::Error(format!(
-                        "Unable to parse string to id: {}",
-                        args[3]
-                    ));
+
+                // Without a last day the range runs up to today
+                let last = match args.get(4).map(String::as_str) {
+                    None | Some("") => Ok(JournalDay::Today),
+                    Some(day) => JournalDay::parse(day),
+                };
+
+                match (JournalDay::parse(&args[3]), last) {
+                    (Ok(first), Ok(last)) => {
+                        CommandType::PrintJournal(JournalPeriod::Range(first, last))
+                    }
+                    (Err(e), _) | (_, Err(e)) => CommandType::Error(e),
                 }
             }
+            Some(_) => {
+                let words: Vec<&str> = args[2..]
+                    .iter()
+                    .map(String::as_str)
+                    .filter(|word| !word.is_empty())
+                    .collect();
+
+                CommandType::AddNoteToJournal(words.join(" "))
+            }
+        };
+    }
+
+    if first_argument == "inbox" && args.len() > 2 && args[2] == "triage" {
+        return CommandType::TriageInbox;
+    }
+
+    if first_argument == "init" {
+        if args.len() < 3 {
+            return CommandType::Error(String::from("Too few arguments."));
+        } else if !SHELLS.contains(&args[2].as_str()) {
+            return CommandType::Error(format!(
+                "Unsupported shell: {}, expected one of {}",
+                args[2],
+                SHELLS.join(", ")
+            ));
+        } else {
+            return CommandType::PrintShellIntegration(args[2].clone());
         }
     }
 
+    if args.len() < 3 {
+        return CommandType::ListNotesInFile(first_argument.to_string(), list_options);
+    }
+
+    let note_filename = first_argument;
+    let second_argument = &args[2];
+
+    if list_args.contains(second_argument) {
+        return CommandType::ListNotesInFile(note_filename.to_string(), list_options);
+    }
+
+    if remove_args.contains(second_argument) {
+        return match note_selector(&args, &match_text) {
+            Ok((selector, _)) => {
+                CommandType::RemoveNoteFromFile(note_filename.to_string(), selector)
+            }
+            Err(e) => CommandType::Error(e),
+        };
+    }
+
     if edit_args.contains(second_argument) {
         if args.len() < 4 {
             return CommandType::Error(String::from("Too few arguments."));
         } else {
-            let note_id_to_edit = args[3].clone().parse::<usize>();
+            let note_id_to_edit = NoteIndex::parse(&args[3]);
 
             match note_id_to_edit {
                 Ok(result) => {
-                    if args.len() < 5 {
-                        return CommandType::Error(String::from("Too few arguments."));
+                    if args.len() == 4 {
+                        return CommandType::EditNoteInEditor(note_filename.to_string(), result);
                     } else {
                         return CommandType::EditNoteInFile(
                             note_filename.to_string(),
@@ -107,14 +489,163 @@ pub fn rn_get_command_type(args: Vec<String>) -> CommandType {
                         );
                     }
                 }
+                Err(e) => {
+                    return CommandType::Error(e);
+                }
+            }
+        }
+    }
+
+    if second_argument == "--encrypt" {
+        return CommandType::EncryptNoteFile(note_filename.to_string());
+    }
+
+    if second_argument == "--decrypt" {
+        return CommandType::DecryptNoteFile(note_filename.to_string());
+    }
+
+    if second_argument == "secret" || second_argument == "unsecret" {
+        if args.len() < 4 {
+            return CommandType::Error(String::from("Too few arguments."));
+        }
+
+        return match NoteIndex::parse(&args[3]) {
+            Ok(id) => CommandType::MarkNoteSecret(
+                note_filename.to_string(),
+                id,
+                second_argument == "secret",
+            ),
+            Err(e) => CommandType::Error(e),
+        };
+    }
+
+    if second_argument == "mv" {
+        let (selector, next) = match note_selector(&args, &match_text) {
+            Ok(selected) => selected,
+            Err(e) => return CommandType::Error(e),
+        };
+
+        return match args.get(next).filter(|arg| !arg.is_empty()) {
+            Some(position) => match NoteIndex::parse(position) {
+                Ok(position) => {
+                    CommandType::MoveNote(note_filename.to_string(), selector, position)
+                }
                 Err(_) => {
-                    return CommandType::Error(format!(
-                        "Unable to parse string to id: {}",
-                        args[4]
-                    ));
+                    CommandType::Error(format!("Unable to parse string to position: {}", position))
                 }
+            },
+            None => CommandType::Error(String::from("Too few arguments.")),
+        };
+    }
+
+    if second_argument == "up" || second_argument == "down" {
+        if args.len() < 4 {
+            return CommandType::Error(String::from("Too few arguments."));
+        }
+
+        let id = match NoteIndex::parse(&args[3]) {
+            Ok(id) => id,
+            Err(e) => return CommandType::Error(e),
+        };
+        let position = match (id, second_argument == "up") {
+            (NoteIndex::FromStart(0), true) => {
+                return CommandType::Error(String::from("Note is already the first one."))
+            }
+            (NoteIndex::FromEnd(1), false) => {
+                return CommandType::Error(String::from("Note is already the last one."))
+            }
+            (NoteIndex::FromStart(index), true) => NoteIndex::FromStart(index - 1),
+            (NoteIndex::FromStart(index), false) => NoteIndex::FromStart(index + 1),
+            (NoteIndex::FromEnd(back), true) => NoteIndex::FromEnd(back + 1),
+            (NoteIndex::FromEnd(back), false) => NoteIndex::FromEnd(back - 1),
+        };
+
+        return CommandType::MoveNote(
+            note_filename.to_string(),
+            NoteSelector::single(id),
+            position,
+        );
+    }
+
+    if second_argument == "sort" {
+        return CommandType::SortNotes(note_filename.to_string(), note_sort);
+    }
+
+    if second_argument == "pin" || second_argument == "unpin" {
+        if args.len() < 4 {
+            return CommandType::Error(String::from("Too few arguments."));
+        }
+
+        return match NoteIndex::parse(&args[3]) {
+            Ok(id) => CommandType::PinNote(note_filename.to_string(), id, second_argument == "pin"),
+            Err(e) => CommandType::Error(e),
+        };
+    }
+
+    if second_argument == "done" || second_argument == "undo" {
+        if args.len() < 4 {
+            return CommandType::Error(String::from("Too few arguments."));
+        }
+
+        return match NoteIndex::parse(&args[3]) {
+            Ok(id) => {
+                CommandType::SetNoteDone(note_filename.to_string(), id, second_argument == "done")
+            }
+            Err(e) => CommandType::Error(e),
+        };
+    }
+
+    if second_argument == "copy" {
+        return match note_selector(&args, &match_text) {
+            Ok((selector, _)) => {
+                CommandType::CopyNoteToClipboard(note_filename.to_string(), selector)
+            }
+            Err(e) => CommandType::Error(e),
+        };
+    }
+
+    if second_argument == "history" {
+        if args.len() < 4 {
+            return CommandType::Error(String::from("Too few arguments."));
+        }
+
+        return match NoteIndex::parse(&args[3]) {
+            Ok(id) => CommandType::ShowNoteHistory(note_filename.to_string(), id),
+            Err(e) => CommandType::Error(e),
+        };
+    }
+
+    if second_argument == "revert" {
+        if args.len() < 5 {
+            return CommandType::Error(String::from("Too few arguments."));
+        }
+
+        let id = match NoteIndex::parse(&args[3]) {
+            Ok(id) => id,
+            Err(e) => return CommandType::Error(e),
+        };
+
+        return match args[4].parse::<usize>() {
+            Ok(revision) => CommandType::RevertNote(note_filename.to_string(), id, revision),
+            Err(_) => {
+                CommandType::Error(format!("Unable to parse string to revision: {}", args[4]))
             }
+        };
+    }
+
+    if second_argument == "last" {
+        return CommandType::AddLastCommandToFile(note_filename.to_string(), with_context);
+    }
+
+    if second_argument == "show" {
+        if args.len() < 4 {
+            return CommandType::Error(String::from("Too few arguments."));
         }
+
+        return match NoteIndex::parse(&args[3]) {
+            Ok(id) => CommandType::ShowNote(note_filename.to_string(), id),
+            Err(e) => CommandType::Error(e),
+        };
     }
 
     if add_args.contains(second_argument) {
@@ -122,17 +653,111 @@ pub fn rn_get_command_type(args: Vec<String>) -> CommandType {
             return CommandType::Error(String::from("Too few arguments."));
         } else {
             let note_to_add = args[3].clone();
+
+            if note_to_add == "-" {
+                return CommandType::AddNotesFromStdin(note_filename.to_string(), multiline);
+            }
+
             return CommandType::AddNoteToFile(note_filename.to_string(), note_to_add);
         }
     }
 
+    // `rn <file> <id>` shows a note, any other text is added as one
+    if let Ok(id) = NoteIndex::parse(second_argument) {
+        return CommandType::ShowNote(note_filename.to_string(), id);
+    }
+
     CommandType::AddNoteToFile(note_filename.to_string(), second_argument.to_string())
 }
 
+/// Removes a flag from the arguments, telling whether it was given.
+fn take_flag(args: &mut Vec<String>, flag: &str) -> bool {
+    let position = options(args).position(|arg| arg == flag);
+    match position {
+        Some(position) => {
+            args.remove(position + 1);
+            true
+        }
+        None => false,
+    }
+}
+
+/// The arguments that may be options, those after the program name and before `--`.
+fn options(args: &[String]) -> impl Iterator<Item = &String> {
+    args.iter().skip(1).take_while(|arg| *arg != OPTION_END)
+}
+
+/// Removes an option with a number of notes as its value from the arguments.
+fn take_count(args: &mut Vec<String>, option: &str) -> Result<Option<usize>, String> {
+    match take_option(args, option)? {
+        Some(count) => match count.parse::<usize>() {
+            Ok(count) => Ok(Some(count)),
+            Err(_) => Err(format!("Unable to parse string to count: {}", count)),
+        },
+        None => Ok(None),
+    }
+}
+
+/// The notes a command is about, given with `--match <text>` or as ids like `2,5,7-9` in the
+/// argument after the command, with the position of the argument that follows them.
+fn note_selector(
+    args: &[String],
+    match_text: &Option<String>,
+) -> Result<(NoteSelector, usize), String> {
+    if let Some(text) = match_text {
+        return Ok((NoteSelector::Matching(text.clone()), 3));
+    }
+
+    match args.get(3).filter(|arg| !arg.is_empty()) {
+        Some(ids) => Ok((NoteSelector::parse(ids)?, 4)),
+        None => Err(String::from("Too few arguments.")),
+    }
+}
+
+/// Removes an option and its value, given as `--option value` or `--option=value`, from the
+/// arguments.
+fn take_option(args: &mut Vec<String>, option: &str) -> Result<Option<String>, String> {
+    let prefix = format!("{}=", option);
+
+    let position = options(args).position(|arg| arg.starts_with(&prefix));
+    if let Some(position) = position {
+        let arg = args.remove(position + 1);
+        return Ok(Some(arg[prefix.len()..].to_string()));
+    }
+
+    let position = options(args).position(|arg| arg == option);
+    match position {
+        Some(position) => {
+            if position + 2 >= args.len() {
+                return Err(format!("Missing value for {}.", option));
+            }
+
+            args.remove(position + 1);
+            Ok(Some(args.remove(position + 1)))
+        }
+        None => Ok(None),
+    }
+}
+
 #[allow(unused_variables)]
 mod parse_command_type_tests {
     #[allow(unused_imports)]
-    use crate::parsearguments::{rn_get_command_type, CommandType};
+    use crate::filelisting::{FileSort, ListFilesOptions};
+    #[allow(unused_imports)]
+    use crate::journal::{JournalDay, JournalPeriod};
+    #[allow(unused_imports)]
+    use crate::notefile::{NoteSort, Priority};
+    #[allow(unused_imports)]
+    use crate::parsearguments::{
+        rn_get_command_type, rn_get_note_options, rn_get_output_options, CommandType, ListOptions,
+        NoteOptions, OutputOptions, DEFAULT_RECENT_COUNT,
+    };
+    #[allow(unused_imports)]
+    use crate::selection::{NoteIndex, NoteSelector};
+    #[allow(unused_imports)]
+    use crate::terminal::ColorChoice;
+    #[allow(unused_imports)]
+    use chrono::NaiveDate;
 
     #[test]
     fn test_help() {
@@ -170,86 +795,876 @@ mod parse_command_type_tests {
     }
 
     #[test]
-    fn test_list_notefiles() {
-        let mut args = fake_args("list", "", "", "");
+    fn test_add_notes_from_stdin() {
+        let mut args = fake_args("notefile", "add", "-", "");
         let mut result = rn_get_command_type(args);
-        assert_eq!(result, CommandType::ListNoteFiles);
-        args = fake_args("l", "", "", "");
+        assert_eq!(
+            result,
+            CommandType::AddNotesFromStdin("notefile".to_string(), false)
+        );
+        args = fake_args("notefile", "a", "-", "--multiline");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::AddNotesFromStdin("notefile".to_string(), true)
+        );
+        args = fake_args("notefile", "--multiline", "a", "-");
         result = rn_get_command_type(args);
-        assert_eq!(result, CommandType::ListNoteFiles);
+        assert_eq!(
+            result,
+            CommandType::AddNotesFromStdin("notefile".to_string(), true)
+        );
     }
 
     #[test]
-    fn test_list_notes_in_notefiles() {
-        let mut args = fake_args("notefile", "list", "", "");
+    fn test_add_last_command() {
+        let mut args = fake_args("notefile", "last", "", "");
         let mut result = rn_get_command_type(args);
-        assert_eq!(result, CommandType::ListNotesInFile("notefile".to_string()));
-        args = fake_args("notefile", "l", "", "");
+        assert_eq!(
+            result,
+            CommandType::AddLastCommandToFile("notefile".to_string(), false)
+        );
+        args = fake_args("notefile", "last", "--context", "");
         result = rn_get_command_type(args);
-        assert_eq!(result, CommandType::ListNotesInFile("notefile".to_string()));
-        args = vec![String::from("rn"), String::from("notefile")];
+        assert_eq!(
+            result,
+            CommandType::AddLastCommandToFile("notefile".to_string(), true)
+        );
+    }
+
+    #[test]
+    fn test_show_history() {
+        let mut args = fake_args("log", "", "", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(result, CommandType::ShowHistory(None));
+        args = fake_args("log", "notefile", "", "");
         result = rn_get_command_type(args);
-        assert_eq!(result, CommandType::ListNotesInFile("notefile".to_string()));
+        assert_eq!(
+            result,
+            CommandType::ShowHistory(Some("notefile".to_string()))
+        );
     }
 
     #[test]
-    fn test_remove_note_from_notefile() {
-        let mut args = fake_args("notefile", "remove", "5", "");
+    fn test_diff_notefiles() {
+        let mut args = fake_args("diff", "notefile", "otherfile", "");
         let mut result = rn_get_command_type(args);
         assert_eq!(
             result,
-            CommandType::RemoveNoteFromFile("notefile".to_string(), 5)
+            CommandType::DiffNoteFiles("notefile".to_string(), "otherfile".to_string())
         );
-        args = fake_args("notefile", "r", "5", "");
+        args = fake_args("diff", "notefile", "--since", "2026-10-01");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::DiffNoteFileSince("notefile".to_string(), "2026-10-01".to_string())
+        );
+        args = vec![
+            "rn".to_string(),
+            "diff".to_string(),
+            "notefile".to_string(),
+            "--since".to_string(),
+        ];
         result = rn_get_command_type(args);
         assert_eq!(
             result,
-            CommandType::RemoveNoteFromFile("notefile".to_string(), 5)
+            CommandType::Error("Missing value for --since.".to_string())
         );
     }
 
     #[test]
-    fn test_edit_note_in_notefile() {
-        let mut args = fake_args("notefile", "edit", "5", "new note content");
+    fn test_restore_notefile() {
+        let mut args = fake_args("restore", "notefile", "abc123", "");
         let mut result = rn_get_command_type(args);
         assert_eq!(
             result,
-            CommandType::EditNoteInFile("notefile".to_string(), 5, "new note content".to_string())
+            CommandType::RestoreNoteFile("notefile".to_string(), "abc123".to_string())
         );
-        args = fake_args("notefile", "e", "5", "new note content");
+        args = vec![
+            "rn".to_string(),
+            "restore".to_string(),
+            "notefile".to_string(),
+        ];
+        result = rn_get_command_type(args);
+        assert_eq!(result, CommandType::Error("Too few arguments.".to_string()));
+    }
+
+    #[test]
+    fn test_print_shell_integration() {
+        let mut args = fake_args("init", "zsh", "", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::PrintShellIntegration("zsh".to_string())
+        );
+        args = fake_args("init", "tcsh", "", "");
         result = rn_get_command_type(args);
         assert_eq!(
             result,
-            CommandType::EditNoteInFile("notefile".to_string(), 5, "new note content".to_string())
+            CommandType::Error(
+                "Unsupported shell: tcsh, expected one of bash, zsh, fish".to_string()
+            )
         );
     }
 
     #[test]
-    fn test_open_notefile_in_editor() {
-        let mut args = fake_args("open", "notefile", "", "");
+    fn test_list_notefiles() {
+        let mut args = fake_args("list", "", "", "");
         let mut result = rn_get_command_type(args);
         assert_eq!(
             result,
-            CommandType::OpenNoteFileInEditor("notefile".to_string())
+            CommandType::ListNoteFiles(ListFilesOptions::default())
         );
-        args = fake_args("o", "notefile", "", "");
+        args = fake_args("l", "", "", "");
         result = rn_get_command_type(args);
         assert_eq!(
             result,
-            CommandType::OpenNoteFileInEditor("notefile".to_string())
+            CommandType::ListNoteFiles(ListFilesOptions::default())
         );
     }
 
     #[test]
-    fn test_remove_notefile() {
-        let mut args = fake_args("remove", "notefile", "", "");
+    fn test_list_notefiles_long() {
+        let mut args = fake_args("list", "--long", "--sort", "mtime");
         let mut result = rn_get_command_type(args);
-        assert_eq!(result, CommandType::RemoveNoteFile("notefile".to_string()));
-        args = fake_args("r", "notefile", "", "");
+        assert_eq!(
+            result,
+            CommandType::ListNoteFiles(ListFilesOptions {
+                long: true,
+                sort: FileSort::Modified
+            })
+        );
+        args = fake_args("l", "--sort=count", "", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ListNoteFiles(ListFilesOptions {
+                long: false,
+                sort: FileSort::Count
+            })
+        );
+        args = fake_args("l", "--sort=size", "", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_list_notes_in_notefiles() {
+        let mut args = fake_args("notefile", "list", "", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ListNotesInFile("notefile".to_string(), ListOptions::default())
+        );
+        args = fake_args("notefile", "l", "", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ListNotesInFile("notefile".to_string(), ListOptions::default())
+        );
+        args = vec![String::from("rn"), String::from("notefile")];
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ListNotesInFile("notefile".to_string(), ListOptions::default())
+        );
+    }
+
+    #[test]
+    fn test_remove_note_from_notefile() {
+        let mut args = fake_args("notefile", "remove", "5", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::RemoveNoteFromFile(
+                "notefile".to_string(),
+                NoteSelector::single(NoteIndex::FromStart(5))
+            )
+        );
+        args = fake_args("notefile", "r", "5", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::RemoveNoteFromFile(
+                "notefile".to_string(),
+                NoteSelector::single(NoteIndex::FromStart(5))
+            )
+        );
+    }
+
+    #[test]
+    fn test_head_and_tail() {
+        let mut args = fake_args("notefile", "l", "--head", "3");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ListNotesInFile(
+                "notefile".to_string(),
+                ListOptions {
+                    head: Some(3),
+                    ..ListOptions::default()
+                }
+            )
+        );
+        args = vec![
+            "rn".to_string(),
+            "notefile".to_string(),
+            "--tail=5".to_string(),
+        ];
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ListNotesInFile(
+                "notefile".to_string(),
+                ListOptions {
+                    tail: Some(5),
+                    ..ListOptions::default()
+                }
+            )
+        );
+        args = fake_args("notefile", "l", "--tail", "x");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_recent_notes() {
+        let mut args = fake_args("recent", "", "", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(result, CommandType::ListRecentNotes(DEFAULT_RECENT_COUNT));
+        args = fake_args("recent", "-n", "20", "");
+        result = rn_get_command_type(args);
+        assert_eq!(result, CommandType::ListRecentNotes(20));
+        args = fake_args("recent", "-n", "", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+        args = fake_args("recent", "20", "", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_show_note() {
+        let mut args = fake_args("notefile", "show", "2", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ShowNote("notefile".to_string(), NoteIndex::FromStart(2))
+        );
+        args = vec!["rn".to_string(), "notefile".to_string(), "-1".to_string()];
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ShowNote("notefile".to_string(), NoteIndex::FromEnd(1))
+        );
+        args = vec![
+            "rn".to_string(),
+            "notefile".to_string(),
+            "2 apples".to_string(),
+        ];
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::AddNoteToFile("notefile".to_string(), "2 apples".to_string())
+        );
+        args = fake_args("notefile", "show", "", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_indices_from_end() {
+        let mut args = fake_args("notefile", "r", "-1", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::RemoveNoteFromFile(
+                "notefile".to_string(),
+                NoteSelector::single(NoteIndex::FromEnd(1))
+            )
+        );
+        args = fake_args("notefile", "e", "last", "new note content");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::EditNoteInFile(
+                "notefile".to_string(),
+                NoteIndex::FromEnd(1),
+                "new note content".to_string()
+            )
+        );
+        args = fake_args("notefile", "done", "-2", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::SetNoteDone("notefile".to_string(), NoteIndex::FromEnd(2), true)
+        );
+        args = fake_args("notefile", "up", "last", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::MoveNote(
+                "notefile".to_string(),
+                NoteSelector::single(NoteIndex::FromEnd(1)),
+                NoteIndex::FromEnd(2)
+            )
+        );
+        args = fake_args("notefile", "down", "last", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+        args = fake_args("notefile", "e", "-0", "text");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_remove_several_notes() {
+        let mut args = fake_args("notefile", "r", "2,5,7-9", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::RemoveNoteFromFile(
+                "notefile".to_string(),
+                NoteSelector::parse("2,5,7-9").unwrap()
+            )
+        );
+        args = fake_args("notefile", "r", "--match", "tmp");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::RemoveNoteFromFile(
+                "notefile".to_string(),
+                NoteSelector::Matching("tmp".to_string())
+            )
+        );
+        args = fake_args("notefile", "r", "--match=", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+        args = fake_args("notefile", "r", "", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+        args = fake_args("notefile", "r", "2,x", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_edit_note_in_notefile() {
+        let mut args = fake_args("notefile", "edit", "5", "new note content");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::EditNoteInFile(
+                "notefile".to_string(),
+                NoteIndex::FromStart(5),
+                "new note content".to_string()
+            )
+        );
+        args = fake_args("notefile", "e", "5", "new note content");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::EditNoteInFile(
+                "notefile".to_string(),
+                NoteIndex::FromStart(5),
+                "new note content".to_string()
+            )
+        );
+    }
+
+    #[test]
+    fn test_edit_note_in_editor() {
+        let mut args = vec![
+            "rn".to_string(),
+            "notefile".to_string(),
+            "edit".to_string(),
+            "5".to_string(),
+        ];
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::EditNoteInEditor("notefile".to_string(), NoteIndex::FromStart(5))
+        );
+        args = vec![
+            "rn".to_string(),
+            "notefile".to_string(),
+            "e".to_string(),
+            "5".to_string(),
+        ];
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::EditNoteInEditor("notefile".to_string(), NoteIndex::FromStart(5))
+        );
+    }
+
+    #[test]
+    fn test_mark_note_secret() {
+        let mut args = fake_args("notefile", "secret", "3", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::MarkNoteSecret("notefile".to_string(), NoteIndex::FromStart(3), true)
+        );
+        args = fake_args("notefile", "unsecret", "3", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::MarkNoteSecret("notefile".to_string(), NoteIndex::FromStart(3), false)
+        );
+    }
+
+    #[test]
+    fn test_add_note_to_inbox() {
+        let mut args = fake_args("-", "call the bank", "", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::AddNoteToInbox("call the bank".to_string())
+        );
+        args = fake_args("add", "call", "the", "bank");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::AddNoteToInbox("call the bank".to_string())
+        );
+        args = fake_args("-", "", "", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_triage_inbox() {
+        let mut args = fake_args("inbox", "triage", "", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(result, CommandType::TriageInbox);
+        args = fake_args("inbox", "l", "", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ListNotesInFile("inbox".to_string(), ListOptions::default())
+        );
+    }
+
+    #[test]
+    fn test_journal() {
+        let mut args = fake_args("journal", "standup at ten", "", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::AddNoteToJournal("standup at ten".to_string())
+        );
+        args = fake_args("journal", "", "", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ListJournal(JournalPeriod::Day(JournalDay::Today))
+        );
+        args = fake_args("journal", "l", "yesterday", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ListJournal(JournalPeriod::Day(JournalDay::Yesterday))
+        );
+        args = fake_args("journal", "l", "last-week", "");
+        result = rn_get_command_type(args);
+        assert_eq!(result, CommandType::ListJournal(JournalPeriod::LastWeek));
+        args = fake_args("journal", "l", "someday", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_print_journal() {
+        let first = JournalDay::Date(NaiveDate::from_ymd_opt(2026, 10, 12).unwrap());
+        let mut args = fake_args("journal", "cat", "2026-10-12", "yesterday");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::PrintJournal(JournalPeriod::Range(first, JournalDay::Yesterday))
+        );
+        args = fake_args("journal", "cat", "2026-10-12", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::PrintJournal(JournalPeriod::Range(first, JournalDay::Today))
+        );
+        args = fake_args("journal", "cat", "", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_todo_notes() {
+        let mut args = fake_args("todo", "done", "3", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::SetNoteDone("todo".to_string(), NoteIndex::FromStart(3), true)
+        );
+        args = fake_args("todo", "undo", "3", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::SetNoteDone("todo".to_string(), NoteIndex::FromStart(3), false)
+        );
+        args = fake_args("todo", "l", "--open", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ListNotesInFile(
+                "todo".to_string(),
+                ListOptions {
+                    open: true,
+                    ..ListOptions::default()
+                }
+            )
+        );
+        args = vec!["rn".to_string(), "todo".to_string(), "--open".to_string()];
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ListNotesInFile(
+                "todo".to_string(),
+                ListOptions {
+                    open: true,
+                    ..ListOptions::default()
+                }
+            )
+        );
+    }
+
+    #[test]
+    fn test_due_commands() {
+        let mut args = fake_args("due", "", "", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(result, CommandType::ListDueNotes);
+        args = fake_args("remind", "", "", "");
+        result = rn_get_command_type(args);
+        assert_eq!(result, CommandType::RemindDueNotes);
+    }
+
+    #[test]
+    fn test_note_options() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let mut args = fake_args("todo", "add", "renew certificates", "--due");
+        args.push("tomorrow".to_string());
+        let mut options = rn_get_note_options(&mut args, today).unwrap();
+        assert_eq!(options.due, Some(NaiveDate::from_ymd_opt(2026, 10, 20)));
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::AddNoteToFile("todo".to_string(), "renew certificates".to_string())
+        );
+
+        args = fake_args("todo", "e", "0", "--due=none");
+        options = rn_get_note_options(&mut args, today).unwrap();
+        assert_eq!(options.due, Some(None));
+        args = fake_args("todo", "l", "", "");
+        assert!(rn_get_note_options(&mut args, today).unwrap().is_empty());
+        args = fake_args("todo", "add", "note", "--due=someday");
+        assert!(rn_get_note_options(&mut args, today).is_err());
+    }
+
+    #[test]
+    fn test_reorder_notes() {
+        let mut args = fake_args("snippets", "mv", "4", "0");
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::MoveNote(
+                "snippets".to_string(),
+                NoteSelector::single(NoteIndex::FromStart(4)),
+                NoteIndex::FromStart(0)
+            )
+        );
+        args = fake_args("snippets", "up", "4", "");
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::MoveNote(
+                "snippets".to_string(),
+                NoteSelector::single(NoteIndex::FromStart(4)),
+                NoteIndex::FromStart(3)
+            )
+        );
+        args = fake_args("snippets", "down", "4", "");
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::MoveNote(
+                "snippets".to_string(),
+                NoteSelector::single(NoteIndex::FromStart(4)),
+                NoteIndex::FromStart(5)
+            )
+        );
+        args = fake_args("snippets", "mv", "--match=ssh", "0");
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::MoveNote(
+                "snippets".to_string(),
+                NoteSelector::Matching("ssh".to_string()),
+                NoteIndex::FromStart(0)
+            )
+        );
+        args = fake_args("snippets", "up", "0", "");
+        assert!(matches!(rn_get_command_type(args), CommandType::Error(_)));
+        args = fake_args("snippets", "mv", "4", "");
+        assert!(matches!(rn_get_command_type(args), CommandType::Error(_)));
+        args = fake_args("snippets", "mv", "4", "top");
+        assert!(matches!(rn_get_command_type(args), CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_sort_notes() {
+        let mut args = fake_args("snippets", "sort", "", "");
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::SortNotes("snippets".to_string(), NoteSort::Text)
+        );
+        args = fake_args("snippets", "sort", "--by", "modified");
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::SortNotes("snippets".to_string(), NoteSort::Modified)
+        );
+        args = fake_args("snippets", "sort", "--by=created", "");
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::SortNotes("snippets".to_string(), NoteSort::Created)
+        );
+        args = fake_args("snippets", "sort", "--by=size", "");
+        assert!(matches!(rn_get_command_type(args), CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_priority() {
+        let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        let mut args = fake_args("snippets", "add", "ssh db", "--priority=high");
+        let mut options = rn_get_note_options(&mut args, today).unwrap();
+        assert_eq!(options.priority, Some(Some(Priority::High)));
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::AddNoteToFile("snippets".to_string(), "ssh db".to_string())
+        );
+
+        args = fake_args("snippets", "e", "0", "--priority=none");
+        options = rn_get_note_options(&mut args, today).unwrap();
+        assert_eq!(options.priority, Some(None));
+        args = fake_args("snippets", "e", "0", "--priority=urgent");
+        assert!(rn_get_note_options(&mut args, today).is_err());
+
+        args = fake_args("snippets", "pin", "4", "");
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::PinNote("snippets".to_string(), NoteIndex::FromStart(4), true)
+        );
+        args = fake_args("snippets", "unpin", "4", "");
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::PinNote("snippets".to_string(), NoteIndex::FromStart(4), false)
+        );
+        args = fake_args("snippets", "pin", "", "");
+        assert!(matches!(rn_get_command_type(args), CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_copy_note() {
+        let mut args = fake_args("notefile", "copy", "2", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::CopyNoteToClipboard(
+                "notefile".to_string(),
+                NoteSelector::single(NoteIndex::FromStart(2))
+            )
+        );
+        args = fake_args("notefile", "copy", "2-3", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::CopyNoteToClipboard(
+                "notefile".to_string(),
+                NoteSelector::parse("2-3").unwrap()
+            )
+        );
+        args = fake_args("notefile", "copy", "x", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_output_options() {
+        let mut args = fake_args("notefile", "--reveal", "l", "");
+        let options = rn_get_output_options(&mut args);
+        assert_eq!(
+            options,
+            Ok(OutputOptions {
+                reveal: true,
+                ..OutputOptions::default()
+            })
+        );
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::ListNotesInFile("notefile".to_string(), ListOptions::default())
+        );
+        args = fake_args("notefile", "l", "", "");
+        assert_eq!(
+            rn_get_output_options(&mut args),
+            Ok(OutputOptions::default())
+        );
+    }
+
+    #[test]
+    fn test_color_option() {
+        let mut args = fake_args("notefile", "--color=never", "l", "");
+        let mut options = rn_get_output_options(&mut args).unwrap();
+        assert_eq!(options.color, Some(ColorChoice::Never));
+        assert_eq!(args, fake_args("notefile", "l", "", "")[..4]);
+        args = fake_args("--color", "always", "notefile", "");
+        options = rn_get_output_options(&mut args).unwrap();
+        assert_eq!(options.color, Some(ColorChoice::Always));
+        args = fake_args("notefile", "--color=sometimes", "", "");
+        assert!(rn_get_output_options(&mut args).is_err());
+    }
+
+    #[test]
+    fn test_no_pager_option() {
+        let mut args = fake_args("notefile", "l", "--no-pager", "");
+        let options = rn_get_output_options(&mut args).unwrap();
+        assert!(options.no_pager);
+        assert_eq!(
+            rn_get_command_type(args),
+            CommandType::ListNotesInFile("notefile".to_string(), ListOptions::default())
+        );
+    }
+
+    #[test]
+    fn test_encrypt_and_decrypt_notefile() {
+        let mut args = fake_args("notefile", "--encrypt", "", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(result, CommandType::EncryptNoteFile("notefile".to_string()));
+        args = fake_args("notefile", "--decrypt", "", "");
+        result = rn_get_command_type(args);
+        assert_eq!(result, CommandType::DecryptNoteFile("notefile".to_string()));
+    }
+
+    #[test]
+    fn test_show_note_history() {
+        let args = fake_args("notefile", "history", "2", "");
+        let result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::ShowNoteHistory("notefile".to_string(), NoteIndex::FromStart(2))
+        );
+    }
+
+    #[test]
+    fn test_revert_note() {
+        let mut args = fake_args("notefile", "revert", "2", "1");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::RevertNote("notefile".to_string(), NoteIndex::FromStart(2), 1)
+        );
+        args = fake_args("notefile", "revert", "2", "latest");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::Error("Unable to parse string to revision: latest".to_string())
+        );
+    }
+
+    #[test]
+    fn test_open_notefile_in_editor() {
+        let mut args = fake_args("open", "notefile", "", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::OpenNoteFileInEditor("notefile".to_string(), None)
+        );
+        args = fake_args("o", "notefile", "", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::OpenNoteFileInEditor("notefile".to_string(), None)
+        );
+    }
+
+    #[test]
+    fn test_open_notefile_in_editor_at_note() {
+        let mut args = fake_args("open", "notefile", "--at", "3");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::OpenNoteFileInEditor(
+                "notefile".to_string(),
+                Some(NoteIndex::FromStart(3))
+            )
+        );
+        args = fake_args("open", "notefile", "--at", "-1");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::OpenNoteFileInEditor("notefile".to_string(), Some(NoteIndex::FromEnd(1)))
+        );
+        args = fake_args("o", "--at=last", "notefile", "");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::OpenNoteFileInEditor("notefile".to_string(), Some(NoteIndex::FromEnd(1)))
+        );
+        args = fake_args("o", "notefile", "--at", "three");
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::Error("Unable to parse string to id: three".to_string())
+        );
+        args = fake_args("notefile", "l", "--at=3", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+    }
+
+    #[test]
+    fn test_remove_notefile() {
+        let mut args = fake_args("remove", "notefile", "", "");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(result, CommandType::RemoveNoteFile("notefile".to_string()));
+        args = fake_args("r", "notefile", "", "");
         result = rn_get_command_type(args);
         assert_eq!(result, CommandType::RemoveNoteFile("notefile".to_string()));
     }
 
+    #[test]
+    fn test_options_end() {
+        let mut args = fake_args("todo", "a", "--", "--open");
+        let mut result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::AddNoteToFile("todo".to_string(), "--open".to_string())
+        );
+        args = fake_args("todo", "a", "--", "--due=tomorrow");
+        let today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+        assert!(rn_get_note_options(&mut args, today).unwrap().is_empty());
+        result = rn_get_command_type(args);
+        assert_eq!(
+            result,
+            CommandType::AddNoteToFile("todo".to_string(), "--due=tomorrow".to_string())
+        );
+    }
+
+    #[test]
+    fn test_options_the_command_does_not_take() {
+        let mut args = fake_args("todo", "l", "--multiline", "");
+        let mut result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+        args = fake_args("list", "--open", "", "");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+        args = fake_args("todo", "r", "0", "--by=priority");
+        result = rn_get_command_type(args);
+        assert!(matches!(result, CommandType::Error(_)));
+        args = fake_args("todo", "l", "--open", "");
+        result = rn_get_command_type(args);
+        assert!(result.takes_option("--open"));
+        assert!(!result.takes_option("--due"));
+    }
+
     #[allow(dead_code)]
     fn fake_args(arg1: &str, arg2: &str, arg3: &str, arg4: &str) -> Vec<String> {
         vec![
@@ -0,0 +1,140 @@
+//! Earlier bodies of edited notes are kept in `.history/<filename>`, in the note format.
+//! Each kept body carries the `history` id of the note it belongs to and the time it was
+//! replaced. Notes are linked to their history by id rather than by index, so the history
+//! stays with a note when the notes around it are removed.
+
+use chrono::Utc;
+
+use crate::notefile::Note;
+
+pub const HISTORY_DIRECTORY: &str = ".history";
+
+/// Note file that keeps the history of the notes in `file`.
+pub fn history_file(file: &str) -> String {
+    format!("{}/{}", HISTORY_DIRECTORY, file)
+}
+
+/// Keeps the current body of `note` in `history` before it is replaced, linking the note to
+/// its history first if it has none yet. The kept body stays secret if the note is.
+pub fn record_revision(history: &mut Vec<Note>, note: &mut Note) {
+    let id = match note.history {
+        Some(id) => id,
+        None => {
+            let id = next_id(history);
+            note.history = Some(id);
+            id
+        }
+    };
+
+    history.push(Note {
+        history: Some(id),
+        replaced: Some(Utc::now()),
+        secret: note.secret,
+        ..Note::new(&note.text)
+    });
+}
+
+/// Adds the earlier bodies of a note moving to another file to `history`, the history of
+/// that file, and links the note to them there. `revisions` are the note's entries in the
+/// history of the file it comes from, whose ids mean nothing in the other file.
+pub fn carry_over(history: &mut Vec<Note>, note: &mut Note, revisions: &[&Note]) {
+    note.history = None;
+
+    if revisions.is_empty() {
+        return;
+    }
+
+    let id = next_id(history);
+    note.history = Some(id);
+    history.extend(revisions.iter().map(|revision| Note {
+        history: Some(id),
+        ..(*revision).clone()
+    }));
+}
+
+/// History id that no note uses yet.
+fn next_id(history: &[Note]) -> u32 {
+    history
+        .iter()
+        .filter_map(|revision| revision.history)
+        .max()
+        .map_or(0, |id| id + 1)
+}
+
+/// Earlier bodies of `note`, oldest first.
+pub fn revisions<'a>(history: &'a [Note], note: &Note) -> Vec<&'a Note> {
+    match note.history {
+        Some(id) => history
+            .iter()
+            .filter(|revision| revision.history == Some(id))
+            .collect(),
+        None => Vec::new(),
+    }
+}
+
+#[allow(unused_variables)]
+mod revisions_tests {
+    #[allow(unused_imports)]
+    use crate::notefile::Note;
+    #[allow(unused_imports)]
+    use crate::revisions::{carry_over, record_revision, revisions};
+
+    #[test]
+    fn test_record_revisions() {
+        let mut history = Vec::new();
+        let mut first = Note::new("first body");
+        let mut second = Note::new("other note");
+
+        record_revision(&mut history, &mut first);
+        first.set_text("second body");
+        record_revision(&mut history, &mut second);
+        record_revision(&mut history, &mut first);
+
+        assert_eq!(first.history, Some(0));
+        assert_eq!(second.history, Some(1));
+
+        let texts: Vec<&str> = revisions(&history, &first)
+            .iter()
+            .map(|revision| revision.text.as_str())
+            .collect();
+        assert_eq!(texts, vec!["first body", "second body"]);
+        assert_eq!(revisions(&history, &Note::new("never edited")).len(), 0);
+    }
+
+    #[test]
+    fn test_carry_over_revisions() {
+        let mut inbox_history = Vec::new();
+        let mut note = Note::new("first body");
+        record_revision(&mut inbox_history, &mut note);
+        note.set_text("second body");
+
+        let mut other = Note::new("other body");
+        let mut target_history = Vec::new();
+        record_revision(&mut target_history, &mut other);
+
+        let moved = revisions(&inbox_history, &note);
+        carry_over(&mut target_history, &mut note, &moved);
+
+        assert_eq!(note.history, Some(1));
+        assert_eq!(revisions(&target_history, &note)[0].text, "first body");
+        assert_eq!(revisions(&target_history, &other)[0].text, "other body");
+
+        let mut unedited = Note::new("never edited");
+        carry_over(&mut target_history, &mut unedited, &[]);
+        assert_eq!(unedited.history, None);
+    }
+
+    #[test]
+    fn test_secret_revisions() {
+        let mut history = Vec::new();
+        let mut note = Note {
+            secret: true,
+            ..Note::new("password=hunter2")
+        };
+
+        record_revision(&mut history, &mut note);
+
+        assert!(history[0].secret);
+        assert_eq!(history[0].text, "password=hunter2");
+    }
+}
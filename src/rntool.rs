@@ -1,11 +1,49 @@
-use std::fs::{self, File, OpenOptions};
-use std::io::{prelude::*, BufReader};
+use std::cell::{Cell, OnceCell, RefCell};
+use std::cmp::{Ordering, Reverse};
+use std::env;
+use std::fs::{self, OpenOptions};
+use std::io::{self, prelude::*};
+use std::path::Path;
 
-use crate::parsearguments::CommandType;
+use chrono::{Local, NaiveDate, Utc};
+
+use crate::clipboard;
+use crate::config::Config;
+use crate::duedate;
+use crate::editor;
+use crate::encryption::{self, Key};
+use crate::filelisting::{self, FileSort, NoteFileInfo};
+use crate::gitrepo;
+use crate::journal::{self, JournalPeriod};
+use crate::masking::Masker;
+use crate::notebooklock::NotebookLock;
+use crate::notediff::{self, NoteChange};
+use crate::notefile::{self, Note, Priority};
+use crate::pager;
+use crate::parsearguments::{CommandType, NoteOptions, OutputOptions};
+use crate::revisions;
+use crate::selection::NoteIndex;
+use crate::shellintegration;
+use crate::terminal::{self, ColorChoice, Terminal};
+
+/// Note file that is a to-do list from its first note.
+const TODO_FILE: &str = "todo";
 
 pub struct RnTool {
     working_directory: String,
     command_type: CommandType,
+    output_options: OutputOptions,
+    note_options: NoteOptions,
+    config: Config,
+    masker: Masker,
+    terminal: OnceCell<Terminal>,
+    secret: OnceCell<Vec<u8>>,
+    key: RefCell<Option<Key>>,
+    exit_status: Cell<i32>,
+    /// Held while the command changes the notebook.
+    lock: RefCell<Option<NotebookLock>>,
+    /// The date of today, taken once so that a command running past midnight sees one day.
+    today: NaiveDate,
 }
 
 impl RnTool {
@@ -19,193 +57,1647 @@ impl RnTool {
         RnTool {
             working_directory,
             command_type,
+            output_options: OutputOptions::default(),
+            note_options: NoteOptions::default(),
+            config: Config::default(),
+            masker: Masker::new(),
+            terminal: OnceCell::new(),
+            secret: OnceCell::new(),
+            key: RefCell::new(None),
+            exit_status: Cell::new(0),
+            lock: RefCell::new(None),
+            today: Local::now().date_naive(),
         }
     }
+
+    pub fn with_output_options(mut self, output_options: OutputOptions) -> RnTool {
+        self.output_options = output_options;
+        self
+    }
+
+    pub fn with_note_options(mut self, note_options: NoteOptions) -> RnTool {
+        self.note_options = note_options;
+        self
+    }
+
+    pub fn with_config(mut self, config: Config) -> RnTool {
+        // Loading the configuration already refuses invalid patterns
+        self.masker = config.masker().unwrap_or_else(|_| Masker::new());
+        self.config = config;
+        self
+    }
+
     pub fn execute_command(&self) -> Result<(), String> {
+        let change = self.change_description();
+
+        if let Some(message) = &change {
+            self.confirm(message)?;
+        }
+
+        // Commands waiting on an editor or on answers only lock while they write
+        if change.is_some() && !self.waits_on_user() {
+            self.lock()?;
+        }
+
+        let result = self.run_command().and_then(|()| match change {
+            Some(message) if gitrepo::is_enabled(&self.working_directory) => {
+                self.lock()?;
+                gitrepo::commit_all(&self.working_directory, &message)
+            }
+            _ => Ok(()),
+        });
+
+        self.unlock();
+        result
+    }
+
+    /// Takes the notebook lock for the rest of the command, unless it is held already.
+    fn lock(&self) -> Result<(), String> {
+        let mut lock = self.lock.borrow_mut();
+
+        if lock.is_none() {
+            *lock = Some(NotebookLock::acquire(&self.working_directory)?);
+        }
+
+        Ok(())
+    }
+
+    fn unlock(&self) {
+        self.lock.borrow_mut().take();
+    }
+
+    /// Whether the command waits on an editor or on answers, during which other rn
+    /// commands have to be able to change the notebook.
+    fn waits_on_user(&self) -> bool {
+        match self.command_type {
+            CommandType::OpenNoteFileInEditor(..) | CommandType::TriageInbox => true,
+            CommandType::EditNoteInEditor(..) => self.note_options.is_empty(),
+            _ => false,
+        }
+    }
+
+    /// Status to exit with after the command ran, non-zero when `rn remind` found overdue
+    /// notes.
+    pub fn exit_status(&self) -> i32 {
+        self.exit_status.get()
+    }
+
+    fn run_command(&self) -> Result<(), String> {
         match &self.command_type {
-            CommandType::ListNoteFiles => {
-                let paths = fs::read_dir(&self.working_directory).unwrap();
+            CommandType::ListNoteFiles(options) => {
+                let files = filelisting::note_files(&self.working_directory, options.sort)?;
+                let name_width = files.iter().map(|file| file.name.chars().count()).max();
+                let count_width = files
+                    .iter()
+                    .filter_map(|file| file.note_count)
+                    .map(|count| count.to_string().len())
+                    .max()
+                    .unwrap_or(1)
+                    .max(3);
+                let mut output = String::new();
 
-                for path in paths {
-                    println!("{}", path.unwrap().file_name().to_str().unwrap())
+                for file in &files {
+                    let name = self.terminal().paint(&file.name, terminal::FILE_COLOR);
+
+                    if options.long {
+                        let padding = name_width.unwrap_or(0) - file.name.chars().count();
+                        output.push_str(&format!(
+                            "{}{}  {}\n",
+                            name,
+                            " ".repeat(padding),
+                            filelisting::long_details(file, count_width)
+                        ));
+                    } else {
+                        output.push_str(&format!("{}\n", name));
+                    }
                 }
 
-                Ok(())
+                self.show(&output)
             }
             CommandType::RemoveNoteFile(file) => {
                 match fs::remove_file(format!("{}{}", &self.working_directory, file)) {
                     Err(e) => Err(e.to_string()),
-                    _ => Ok(()),
+                    _ => {
+                        let _ =
+                            fs::remove_file(self.note_file_path(&revisions::history_file(file)));
+                        Ok(())
+                    }
                 }
             }
-            CommandType::OpenNoteFileInEditor(file) => {
-                edit::edit_file(format!("{}{}", &self.working_directory, file)).unwrap();
-                Ok(())
-            }
-            CommandType::ListNotesInFile(file) => {
-                let file_result = File::open(format!("{}{}", &self.working_directory, file));
+            CommandType::OpenNoteFileInEditor(file, at) => {
+                let path = self.note_file_path(file);
 
-                let file: File = match file_result {
-                    Ok(f) => {
-                         f
-                    }
-                    Err(_) => {
-                        return Err("File did not exist".to_string());
+                let at = match at {
+                    Some(index) => {
+                        let count = self.read_note_file(file)?.lines().count();
+                        let line_number = index.resolve(count);
+
+                        if line_number >= count {
+                            return Err("Unable to find line that user wanted to open.".to_string());
+                        }
+
+                        Some(line_number)
                     }
+                    None => None,
                 };
 
-                let reader = BufReader::new(file);
+                // Decrypted notes are edited in memory and only written back encrypted
+                if self.is_encrypted(file) {
+                    let content = self.read_note_file(file)?;
+                    let edited = editor::edit_privately(
+                        self.config.editor.as_deref(),
+                        &content,
+                        at.map(|line| line + 1),
+                    )?;
+
+                    if let Err(e) = notefile::check_note_format(&edited) {
+                        return Err(format!(
+                            "The edited notes are not a valid note file, {}, {} was left as it was.",
+                            e, file
+                        ));
+                    }
+
+                    self.lock()?;
+
+                    if self.read_note_file(file)? != content {
+                        return Err(format!(
+                            "{} changed while it was being edited, the edits were left out.",
+                            file
+                        ));
+                    }
+
+                    return self.write_note_file(file, &edited, true);
+                }
+
+                editor::edit_file(
+                    self.config.editor.as_deref(),
+                    &path,
+                    at.map(|line| line + 1),
+                )?;
+
+                if let Ok(content) = fs::read_to_string(&path) {
+                    if let Err(e) = notefile::check_note_format(&content) {
+                        eprintln!("Warning: {} is no longer a valid note file, {}.", file, e);
+                    }
+                }
+
+                Ok(())
+            }
+            CommandType::ListNotesInFile(file, options) => {
+                if fs::metadata(self.note_file_path(file)).is_err() {
+                    return Err("File did not exist".to_string());
+                }
+
+                let notes = self.read_notes(file)?;
 
-                let mut index: usize = 0;
+                // Every note of a to-do list gets a checkbox
+                let is_todo_list = is_todo_list(file, &notes);
+                let index_width = notes.len().saturating_sub(1).to_string().len();
+                let mut output = String::new();
+
+                let mut order: Vec<usize> = (0..notes.len())
+                    .filter(|&index| !(options.open && notes[index].done == Some(true)))
+                    .collect();
+
+                if let Some(head) = options.head {
+                    order.truncate(head);
+                }
+                if let Some(tail) = options.tail {
+                    order.drain(..order.len().saturating_sub(tail));
+                }
+
+                // Pinned and high priority notes come first, keeping their indices
+                order.sort_by_key(|&index| listing_rank(&notes[index]));
+
+                for index in order {
+                    let note = &notes[index];
+                    let done = note.done == Some(true);
+
+                    let index = format!("{:>width$}", index, width = index_width);
+                    let index = self.terminal().paint(&index, terminal::INDEX_COLOR);
+                    let (mut prefix, suffix) = self.config.list_prefix_and_suffix(&index);
+
+                    if is_todo_list {
+                        prefix.push_str(if done { "[x] " } else { "[ ] " });
+                    }
 
-                for line in reader.lines() {
-                    println!("{}: {}", index, line.unwrap());
-                    index += 1;
+                    output.push_str(&self.format_note(&prefix, note, &suffix));
                 }
 
-                if index == 0 {
+                if notes.is_empty() {
                     return Err("File was empty.".to_string());
                 }
 
+                self.show(&output)
+            }
+            CommandType::ShowNote(file, line_number) => {
+                print!("{}", self.format_single_note(file, line_number)?);
+                Ok(())
+            }
+            CommandType::ListDueNotes => {
+                self.show(&self.format_due_notes(&self.due_notes()?, None))
+            }
+            CommandType::ListRecentNotes(count) => {
+                let mut output = String::new();
+
+                for (file, index, note) in self.recent_notes(*count)? {
+                    let location = format!(
+                        "{} {}: ",
+                        self.terminal().paint(&file, terminal::FILE_COLOR),
+                        self.terminal()
+                            .paint(&index.to_string(), terminal::INDEX_COLOR)
+                    );
+                    output.push_str(&self.format_note(&location, &note, ""));
+                }
+
+                self.show(&output)
+            }
+            CommandType::ListJournal(period) => {
+                let mut output = String::new();
+
+                for (file, notes) in self.journal_notes(period)? {
+                    output.push_str(&format!(
+                        "{}\n",
+                        self.terminal().paint(&file, terminal::FILE_COLOR)
+                    ));
+
+                    for (index, note) in notes.iter().enumerate() {
+                        let index = self
+                            .terminal()
+                            .paint(&index.to_string(), terminal::INDEX_COLOR);
+                        let (prefix, suffix) = self.config.list_prefix_and_suffix(&index);
+                        output.push_str(&self.format_note(&format!("  {}", prefix), note, &suffix));
+                    }
+                }
+
+                self.show(&output)
+            }
+            CommandType::PrintJournal(period) => {
+                print!("{}", self.format_journal(period)?);
+                Ok(())
+            }
+            CommandType::RemindDueNotes => {
+                let today = self.today;
+                let due_notes = self.due_notes()?;
+                print!("{}", self.format_due_notes(&due_notes, Some(today)));
+
+                if due_notes.iter().any(|(_, _, note)| note.due < Some(today)) {
+                    self.exit_status.set(1);
+                }
+
                 Ok(())
             }
             CommandType::AddNoteToFile(file, note) => {
-                let mut file = OpenOptions::new()
-                    .write(true)
-                    .append(true)
-                    .create(true)
-                    .open(format!("{}{}", &self.working_directory, file))
-                    .unwrap();
+                self.append_notes(file, &[self.new_note(note)])
+            }
+            CommandType::AddNoteToInbox(note) => {
+                self.append_notes(self.config.inbox_file(), &[self.new_note(note)])
+            }
+            CommandType::AddNoteToJournal(note) => {
+                self.require_journal_directory()?;
+
+                let file = journal::journal_file(self.today);
+                self.append_notes(&file, &[self.new_note(note)])
+            }
+            CommandType::TriageInbox => self.triage_inbox(&mut io::stdin().lock()),
+            CommandType::AddNotesFromStdin(file, multiline) => {
+                let mut input = String::new();
+
+                if let Err(e) = io::stdin().read_to_string(&mut input) {
+                    return Err(format!("Unable to read standard input: {}", e));
+                }
+
+                let mut notes = notes_from_input(&input, *multiline);
 
-                if let Err(e) = writeln!(file, "{}", note) {
-                    return Err(e.to_string());
+                for note in &mut notes {
+                    self.note_options.apply(note);
                 }
 
+                if notes.is_empty() {
+                    return Err("Nothing to add, standard input was empty.".to_string());
+                }
+
+                self.append_notes(file, &notes)
+            }
+            CommandType::AddLastCommandToFile(file, with_context) => {
+                let cwd = env::current_dir()
+                    .ok()
+                    .map(|path| path.to_string_lossy().into_owned());
+
+                let mut note = shellintegration::last_command_note(
+                    env::var("RN_LAST_COMMAND").ok(),
+                    env::var("RN_LAST_STATUS").ok(),
+                    cwd,
+                    *with_context,
+                )?;
+                self.note_options.apply(&mut note);
+
+                let text = note.text.clone();
+
+                self.append_notes(file, &[note])?;
+                println!("Added to {}: {}", file, text);
+
                 Ok(())
             }
             CommandType::EditNoteInFile(file, line_number, new_content) => {
-                let content= match fs::read_to_string(format!("{}{}", &self.working_directory, file)) {
-                    Ok(c) => {
-                        c
+                let mut notes = self.read_notes(file)?;
+                let line_number = &line_number.resolve(notes.len());
+
+                if *line_number >= notes.len() {
+                    return Err("Unable to find line that user wanted to edit.".to_string());
+                }
+
+                self.note_options.apply(&mut notes[*line_number]);
+
+                if notes[*line_number].text == *new_content {
+                    return self.write_notes(file, &notes);
+                }
+
+                self.replace_note_text(file, &mut notes, *line_number, new_content)
+            }
+            CommandType::EditNoteInEditor(file, line_number) => {
+                let mut notes = self.read_notes(file)?;
+                let line_number = &line_number.resolve(notes.len());
+
+                // `rn <file> edit <index> --due <date>` only changes the given attributes
+                if !self.note_options.is_empty() {
+                    return match notes.get_mut(*line_number) {
+                        Some(note) => {
+                            self.note_options.apply(note);
+                            self.write_notes(file, &notes)
+                        }
+                        None => Err("Unable to find line that user wanted to edit.".to_string()),
+                    };
+                }
+
+                let note = match notes.get(*line_number) {
+                    Some(note) => note.clone(),
+                    None => {
+                        return Err("Unable to find line that user wanted to edit.".to_string());
                     }
-                    Err(e) => {
-                        return Err(e.to_string());
+                };
+
+                let edited = if self.is_encrypted(file) {
+                    editor::edit_privately(self.config.editor.as_deref(), &note.text, None)?
+                } else {
+                    editor::edit_text(self.config.editor.as_deref(), &note.text)?
+                };
+                let new_content = edited.trim_end_matches(['\n', '\r']);
+
+                if new_content.trim().is_empty() {
+                    println!("Note was left empty, edit cancelled.");
+                    return Ok(());
+                }
+
+                self.lock()?;
+                let mut notes = self.read_notes(file)?;
+
+                if notes.get(*line_number) != Some(&note) {
+                    return Err(format!(
+                        "Note {} in {} changed while it was being edited, the edit was left out.",
+                        line_number, file
+                    ));
+                }
+
+                self.replace_note_text(file, &mut notes, *line_number, new_content)
+            }
+            CommandType::ShowNoteHistory(file, line_number) => {
+                let notes = self.read_notes(file)?;
+                let line_number = &line_number.resolve(notes.len());
+
+                let note = match notes.get(*line_number) {
+                    Some(note) => note,
+                    None => return Err("Unable to find note that user wanted to show.".to_string()),
+                };
+
+                let history = self.read_history(file)?;
+                let revisions = revisions::revisions(&history, note);
+
+                if revisions.is_empty() {
+                    return Err(format!(
+                        "Note {} in {} has not been edited.",
+                        line_number, file
+                    ));
+                }
+
+                let mut output = String::new();
+
+                for (revision_number, revision) in revisions.iter().enumerate() {
+                    let replaced = match revision.replaced {
+                        Some(replaced) => replaced
+                            .with_timezone(&Local)
+                            .format("%Y-%m-%d %H:%M")
+                            .to_string(),
+                        None => String::from("unknown time"),
+                    };
+                    // Earlier bodies of a secret note are as secret as the note itself
+                    let revision = Note {
+                        secret: revision.secret || note.secret,
+                        ..(*revision).clone()
+                    };
+
+                    output.push_str(&self.format_note(
+                        &format!("{}: {}  ", revision_number, replaced),
+                        &revision,
+                        "",
+                    ));
+                }
+
+                self.show(&output)
+            }
+            CommandType::RevertNote(file, line_number, revision_number) => {
+                let mut notes = self.read_notes(file)?;
+                let line_number = &line_number.resolve(notes.len());
+
+                let note = match notes.get(*line_number) {
+                    Some(note) => note,
+                    None => {
+                        return Err("Unable to find note that user wanted to revert.".to_string())
+                    }
+                };
+
+                let history = self.read_history(file)?;
+                let text = match revisions::revisions(&history, note).get(*revision_number) {
+                    Some(revision) => revision.text.clone(),
+                    None => {
+                        return Err(format!(
+                            "Note {} in {} has no revision {}.",
+                            line_number, file, revision_number
+                        ))
+                    }
+                };
+
+                self.replace_note_text(file, &mut notes, *line_number, &text)
+            }
+            CommandType::RemoveNoteFromFile(file, selector) => {
+                let mut notes = self.read_notes(file)?;
+
+                // Removing from the end keeps the indices of the notes still to remove
+                for index in selector.resolve(&notes)?.into_iter().rev() {
+                    notes.remove(index);
+                }
+
+                self.write_notes(file, &notes)
+            }
+            CommandType::MarkNoteSecret(file, line_number, secret) => {
+                let mut notes = self.read_notes(file)?;
+                let line_number = &line_number.resolve(notes.len());
+
+                match notes.get_mut(*line_number) {
+                    Some(note) => note.secret = *secret,
+                    None => return Err("Unable to find note that user wanted to mark.".to_string()),
+                }
+
+                self.write_notes(file, &notes)
+            }
+            CommandType::MoveNote(file, selector, position) => {
+                let mut notes = self.read_notes(file)?;
+                let selected = selector.resolve(&notes)?;
+                let position = &position.resolve(notes.len());
+
+                if position + selected.len() > notes.len() {
+                    return Err(format!(
+                        "Unable to move {} to {}, the file has {} notes.",
+                        selector,
+                        position,
+                        notes.len()
+                    ));
+                }
+
+                // The moved notes keep their order and start at the given position
+                let moved: Vec<Note> = selected.iter().map(|&index| notes[index].clone()).collect();
+                for index in selected.into_iter().rev() {
+                    notes.remove(index);
+                }
+                notes.splice(*position..*position, moved);
+
+                self.write_notes(file, &notes)
+            }
+            CommandType::SortNotes(file, sort) => {
+                let mut notes = self.read_notes(file)?;
+                notefile::sort_notes(&mut notes, *sort);
+
+                self.write_notes(file, &notes)
+            }
+            CommandType::PinNote(file, line_number, pinned) => {
+                let mut notes = self.read_notes(file)?;
+                let line_number = &line_number.resolve(notes.len());
+
+                match notes.get_mut(*line_number) {
+                    Some(note) => note.pinned = *pinned,
+                    None => return Err("Unable to find note that user wanted to pin.".to_string()),
+                }
+
+                self.write_notes(file, &notes)
+            }
+            CommandType::SetNoteDone(file, line_number, done) => {
+                let mut notes = self.read_notes(file)?;
+                let line_number = &line_number.resolve(notes.len());
+
+                let note = match notes.get_mut(*line_number) {
+                    Some(note) => note,
+                    None => return Err("Unable to find note that user wanted to mark.".to_string()),
+                };
+
+                note.done = Some(*done);
+                note.completed = if *done { Some(Utc::now()) } else { None };
+
+                self.write_notes(file, &notes)
+            }
+            CommandType::CopyNoteToClipboard(file, selector) => {
+                let notes = self.read_notes(file)?;
+                let texts: Vec<&str> = selector
+                    .resolve(&notes)?
+                    .into_iter()
+                    .map(|index| notes[index].text.as_str())
+                    .collect();
+
+                clipboard::copy(&texts.join("\n"), self.config.clipboard_command.as_deref())
+            }
+            CommandType::PrintHelp => {
+                println!(
+                    "rn is a simple utility for taking and storing notes using the command line."
+                );
+                println!();
+                println!("Show help:");
+                println!("rn help");
+                println!("rn h");
+                println!("rn");
+                println!();
+                println!("Add a new note:");
+                println!("rn <filename> <note>");
+                println!("rn <filename> add <note>");
+                println!("rn <filename> a <note>");
+                println!();
+                println!("Add a new note to the inbox:");
+                println!("rn - <note>");
+                println!("rn add <note>");
+                println!();
+                println!("Move notes out of the inbox one by one:");
+                println!("rn inbox triage");
+                println!();
+                println!("Add a note to the journal file of today, journal/<date>:");
+                println!("rn journal <note>");
+                println!();
+                println!("List the journal of today, another day or the last seven days:");
+                println!("rn journal l");
+                println!("rn journal l <date|yesterday|last-week>");
+                println!();
+                println!("Print the journal of a range of days as plain text:");
+                println!("rn journal cat <first date> [<last date>]");
+                println!();
+                println!("Add notes from standard input, one per line or as a single note:");
+                println!("<command> | rn <filename> add -");
+                println!("<command> | rn <filename> add - --multiline");
+                println!();
+                println!("List all notes:");
+                println!("rn list");
+                println!("rn l");
+                println!();
+                println!("List note files with their note counts, modification times and sizes, sorted by name, mtime or count:");
+                println!("rn list --long");
+                println!("rn list --long --sort mtime");
+                println!();
+                println!("List all entries inside a note file:");
+                println!("rn <filename> list");
+                println!("rn <filename> l");
+                println!("rn <filename>");
+                println!();
+                println!("List only the first or the last notes of a file:");
+                println!("rn <filename> list --head <count>");
+                println!("rn <filename> list --tail <count>");
+                println!();
+                println!("List the most recently added or edited notes of all files:");
+                println!("rn recent");
+                println!("rn recent -n <count>");
+                println!();
+                println!("Print the text of a single note, e.g. for $(...):");
+                println!("rn <filename> show <index>");
+                println!("rn <filename> <index>");
+                println!();
+                println!("Remove an entry from a file:");
+                println!("rn <filename> remove <index>");
+                println!("rn <filename> r <index>");
+                println!();
+                println!("Remove, move or copy several notes at once, by indices and ranges or by the text they contain:");
+                println!("rn <filename> r 2,5,7-9");
+                println!("rn <filename> r --match <text>");
+                println!("rn <filename> mv 2,5 <new index>");
+                println!("rn <filename> copy 7-9");
+                println!();
+                println!("Count indices from the end, with -1 or last for the last note:");
+                println!("rn <filename> e last <newtext>");
+                println!("rn <filename> r -1");
+                println!();
+                println!("Edit note:");
+                println!("rn <filename> edit <index> <newtext>");
+                println!("rn <filename> e <index> <newtext>");
+                println!();
+                println!("Edit a single note in a text editor:");
+                println!("rn <filename> edit <index>");
+                println!("rn <filename> e <index>");
+                println!();
+                println!("Show earlier texts of an edited note:");
+                println!("rn <filename> history <index>");
+                println!();
+                println!("Bring back an earlier text of a note:");
+                println!("rn <filename> revert <index> <revision>");
+                println!();
+                println!("Open note file in a text editor:");
+                println!("rn open <filename>");
+                println!("rn o <filename>");
+                println!();
+                println!("Open note file in a text editor at a note:");
+                println!("rn open <filename> --at <index>");
+                println!("rn o <filename> --at <index>");
+                println!();
+                println!("Save the previous shell command as a note, optionally with its exit status and directory:");
+                println!("rn <filename> last");
+                println!("rn <filename> last --context");
+                println!();
+                println!(
+                    "Print shell integration for saving previous commands (bash, zsh or fish):"
+                );
+                println!("eval \"$(rn init bash)\"");
+                println!("eval \"$(rn init zsh)\"");
+                println!("rn init fish | source");
+                println!();
+                println!("Show version history of all note files or one note file:");
+                println!("rn log");
+                println!("rn log <filename>");
+                println!();
+                println!("Show notes added, removed and changed between two note files:");
+                println!("rn diff <filename> <otherfilename>");
+                println!();
+                println!("Show notes added, removed and changed since a revision or a date:");
+                println!("rn diff <filename> --since <revision|date>");
+                println!();
+                println!("Restore note file to an earlier revision:");
+                println!("rn restore <filename> <revision>");
+                println!();
+                println!("Give a note a due date when adding or editing it, or remove it with --due none:");
+                println!("rn <filename> add <note> --due 2026-11-01");
+                println!("rn <filename> edit <index> --due tomorrow");
+                println!();
+                println!("List notes with a due date in all note files:");
+                println!("rn due");
+                println!();
+                println!("Show notes due today or overdue, exiting with an error status if any are overdue:");
+                println!("rn remind");
+                println!();
+                println!("Move a note to another position, or one step up or down:");
+                println!("rn <filename> mv <index> <new index>");
+                println!("rn <filename> up <index>");
+                println!("rn <filename> down <index>");
+                println!();
+                println!("Sort the notes of a file by text, by when they were added or by when they were last edited:");
+                println!("rn <filename> sort --by text");
+                println!("rn <filename> sort --by created");
+                println!("rn <filename> sort --by modified");
+                println!();
+                println!("Pin a note to the top of listings, or unpin it:");
+                println!("rn <filename> pin <index>");
+                println!("rn <filename> unpin <index>");
+                println!();
+                println!("Give a note a priority of high, med or low when adding or editing it, or remove it with --priority none:");
+                println!("rn <filename> add <note> --priority high");
+                println!("rn <filename> edit <index> --priority low");
+                println!();
+                println!("Check off a to-do note, or open it again:");
+                println!("rn <filename> done <index>");
+                println!("rn <filename> undo <index>");
+                println!();
+                println!("List only the to-do notes that are not done:");
+                println!("rn <filename> list --open");
+                println!();
+                println!("Copy a note to the clipboard:");
+                println!("rn <filename> copy <index>");
+                println!();
+                println!("Mark a note as secret so it is shown as **** in listings, or unmark it:");
+                println!("rn <filename> secret <index>");
+                println!("rn <filename> unsecret <index>");
+                println!();
+                println!("Show secrets in listings instead of masking them:");
+                println!("rn <filename> list --reveal");
+                println!();
+                println!("List without the pager, which is otherwise used when a listing does not fit the terminal:");
+                println!("rn <filename> list --no-pager");
+                println!();
+                println!("Color listings always, never or only on a terminal (the default):");
+                println!("rn <filename> list --color=always|never|auto");
+                println!();
+                println!("Encrypt or decrypt a note file with a passphrase:");
+                println!("rn <filename> --encrypt");
+                println!("rn <filename> --decrypt");
+                println!();
+                println!("Remove note file and all the notes inside it:");
+                println!("rn remove <filename>");
+                println!("rn r <filename>");
+                Ok(())
+            }
+            CommandType::ShowHistory(file) => {
+                self.require_git()?;
+                print!(
+                    "{}",
+                    gitrepo::log(&self.working_directory, file.as_deref())?
+                );
+                Ok(())
+            }
+            CommandType::DiffNoteFiles(old_file, new_file) => {
+                let old_notes = self.read_notes(old_file)?;
+                let new_notes = self.read_notes(new_file)?;
+
+                self.show(&self.format_changes(&notediff::diff_notes(&old_notes, &new_notes)))
+            }
+            CommandType::DiffNoteFileSince(file, since) => {
+                self.require_git()?;
+
+                let old_notes = match gitrepo::file_at(&self.working_directory, file, since)? {
+                    Some(content) => {
+                        let content = self.decrypt_content(file, content)?;
+                        self.parse_notes(file, &content)?
                     }
+                    None => Vec::new(),
+                };
+                let new_notes = match fs::metadata(self.note_file_path(file)) {
+                    Ok(_) => self.read_notes(file)?,
+                    Err(_) => Vec::new(),
                 };
 
-                let mut lines: Vec<&str> = content.lines().collect();
-                let mut was_line_edited: bool = false;
-                for (i, _) in lines.iter_mut().enumerate() {
-                    if i == *line_number {
-                        lines[i] = new_content;
-                        was_line_edited = true;
-                        break;
-                    }
-                }
+                self.show(&self.format_changes(&notediff::diff_notes(&old_notes, &new_notes)))
+            }
+            CommandType::RestoreNoteFile(file, revision) => {
+                self.require_git()?;
+                gitrepo::restore(&self.working_directory, file, revision)
+            }
+            CommandType::EncryptNoteFile(file) => {
+                if self.is_encrypted(file) {
+                    return Err(format!("{} is already encrypted.", file));
+                }
+
+                self.secret(true)?;
+
+                if fs::metadata(self.note_file_path(file)).is_err() {
+                    return self.write_note_file(file, "", true);
+                }
+
+                self.set_encrypted(file, true)
+            }
+            CommandType::DecryptNoteFile(file) => {
+                if !self.is_encrypted(file) {
+                    return Err(format!("{} is not encrypted.", file));
+                }
+
+                self.set_encrypted(file, false)
+            }
+            CommandType::PrintShellIntegration(shell) => {
+                match shellintegration::integration_script(shell) {
+                    Some(script) => {
+                        print!("{}", script);
+                        Ok(())
+                    }
+                    None => Err(format!("Unsupported shell: {}", shell)),
+                }
+            }
+            CommandType::Error(error) => Err(error.clone()),
+        }
+    }
+
+    /// A new note with the attributes given on the command line.
+    fn new_note(&self, text: &str) -> Note {
+        let mut note = Note::new(text);
+        self.note_options.apply(&mut note);
+        note
+    }
+
+    /// The notes of every note file. Encrypted files are left out so that looking through
+    /// all notes never asks for a passphrase, and files that can not be read are left out
+    /// with a warning so that one broken file does not stop `rn remind`.
+    fn all_notes(&self) -> Result<Vec<(NoteFileInfo, Vec<Note>)>, String> {
+        let mut all_notes = Vec::new();
+
+        for file in filelisting::note_files(&self.working_directory, FileSort::Name)? {
+            if file.encrypted {
+                continue;
+            }
+
+            let notes = self.read_note_file(&file.name).and_then(|content| {
+                notefile::parse_notes(&content).map_err(|e| format!("not a valid note file, {}", e))
+            });
+
+            match notes {
+                Ok(notes) => all_notes.push((file, notes)),
+                Err(e) => eprintln!("Warning: leaving out {}, {}.", file.name, e),
+            }
+        }
+
+        Ok(all_notes)
+    }
+
+    /// Notes with a due date that are not done, from every note file, soonest first.
+    fn due_notes(&self) -> Result<Vec<(String, usize, Note)>, String> {
+        let mut due_notes = Vec::new();
+
+        for (file, notes) in self.all_notes()? {
+            for (index, note) in notes.into_iter().enumerate() {
+                if note.due.is_some() && note.done != Some(true) {
+                    due_notes.push((file.name.clone(), index, note));
+                }
+            }
+        }
+
+        due_notes.sort_by_key(|(_, _, note)| note.due);
+        Ok(due_notes)
+    }
+
+    /// Lists the notes due by `until`, or every due note, with the overdue ones highlighted.
+    fn format_due_notes(
+        &self,
+        due_notes: &[(String, usize, Note)],
+        until: Option<NaiveDate>,
+    ) -> String {
+        let today = self.today;
+        let mut output = String::new();
+
+        for (file, index, note) in due_notes {
+            let mut note = note.clone();
+            // The date leads the line, so the note does not repeat it
+            let due = note.due.take().unwrap_or(today);
+
+            if until.is_some_and(|until| due > until) {
+                continue;
+            }
+
+            let date = due.format(duedate::DATE_FORMAT).to_string();
+            let date = match due.cmp(&today) {
+                Ordering::Less => self.terminal().paint(&date, terminal::OVERDUE_COLOR),
+                Ordering::Equal => self.terminal().paint(&date, terminal::INDEX_COLOR),
+                Ordering::Greater => date,
+            };
+            let location = format!(
+                "{} {}",
+                self.terminal().paint(file, terminal::FILE_COLOR),
+                self.terminal()
+                    .paint(&index.to_string(), terminal::INDEX_COLOR)
+            );
+
+            output.push_str(&self.format_note(&format!("{}  {}: ", date, location), &note, ""));
+        }
+
+        output
+    }
+
+    /// The `count` most recently added or edited notes of all note files, newest first.
+    /// Notes from before these times were kept come after the rest, the last notes of the
+    /// most recently changed files first.
+    fn recent_notes(&self, count: usize) -> Result<Vec<(String, usize, Note)>, String> {
+        let mut recent_notes = Vec::new();
+
+        for (file, notes) in self.all_notes()? {
+            for (index, note) in notes.into_iter().enumerate() {
+                let newest_first = Reverse((note.modified.or(note.created), file.modified, index));
+                recent_notes.push((newest_first, file.name.clone(), index, note));
+            }
+        }
+
+        recent_notes.sort_by_key(|(newest_first, _, _, _)| *newest_first);
+        recent_notes.truncate(count);
+
+        Ok(recent_notes
+            .into_iter()
+            .map(|(_, file, index, note)| (file, index, note))
+            .collect())
+    }
+
+    /// The notes of the days in `period` as plain text, day by day, to be pasted elsewhere.
+    fn format_journal(&self, period: &JournalPeriod) -> Result<String, String> {
+        let days: Vec<String> = self
+            .journal_notes(period)?
+            .into_iter()
+            .map(|(file, notes)| {
+                let date = file.rsplit('/').next().unwrap_or(&file).to_string();
+                let texts: Vec<String> = notes.iter().map(|note| self.visible_text(note)).collect();
+                format!("{}\n{}\n", date, texts.join("\n"))
+            })
+            .collect();
+
+        Ok(days.join("\n"))
+    }
+
+    /// Fails when a note file called `journal` takes the place of the journal directory.
+    fn require_journal_directory(&self) -> Result<(), String> {
+        let path = self.note_file_path(journal::JOURNAL_DIRECTORY);
+
+        if Path::new(&path).is_file() {
+            Err(format!(
+                "The note file {} is where the journal keeps its files, rename it to use the journal, e.g. with mv {} {}-notes",
+                journal::JOURNAL_DIRECTORY,
+                path,
+                path
+            ))
+        } else {
+            Ok(())
+        }
+    }
+
+    /// The journal files of the days in `period` that have notes, with their notes.
+    fn journal_notes(&self, period: &JournalPeriod) -> Result<Vec<(String, Vec<Note>)>, String> {
+        self.require_journal_directory()?;
+
+        // Only the days that have a file are read, however wide the period is
+        let (first, last) = period.bounds(self.today)?;
+        let mut dates: Vec<NaiveDate> =
+            match fs::read_dir(self.note_file_path(journal::JOURNAL_DIRECTORY)) {
+                Ok(entries) => entries
+                    .filter_map(|entry| entry.ok())
+                    .filter(|entry| entry.file_type().is_ok_and(|kind| kind.is_file()))
+                    .filter_map(|entry| entry.file_name().to_str().and_then(journal::journal_date))
+                    .filter(|date| (first..=last).contains(date))
+                    .collect(),
+                Err(_) => Vec::new(),
+            };
+        dates.sort();
+
+        let mut journal_notes = Vec::new();
+
+        for date in dates {
+            let file = journal::journal_file(date);
+            let notes = self.read_notes(&file)?;
+
+            if !notes.is_empty() {
+                journal_notes.push((file, notes));
+            }
+        }
+
+        if journal_notes.is_empty() {
+            return Err("No journal notes for those days.".to_string());
+        }
+
+        Ok(journal_notes)
+    }
+
+    /// Goes through the inbox one note at a time, moving each note to the file named in
+    /// `input`, deleting it or keeping it in the inbox.
+    fn triage_inbox(&self, input: &mut dyn BufRead) -> Result<(), String> {
+        let inbox = self.config.inbox_file();
+        let mut notes = self.read_notes(inbox)?;
+        let mut index = 0;
+
+        if notes.is_empty() {
+            println!("Inbox is empty.");
+        }
+
+        while index < notes.len() {
+            print!(
+                "{}",
+                self.format_note(&format!("{}: ", index), &notes[index], "")
+            );
+            print!("Move to file, [s]kip, [d]elete or [q]uit: ");
+            let _ = io::stdout().flush();
+
+            let mut answer = String::new();
+
+            if let Err(e) = input.read_line(&mut answer) {
+                return Err(e.to_string());
+            }
+
+            match answer.trim() {
+                "" | "s" => index += 1,
+                "q" => break,
+                file if file == inbox => index += 1,
+                target => {
+                    // The inbox is only locked while the answer is carried out
+                    self.lock()?;
+                    let result = self.triage_note(inbox, &notes[index], target);
+                    self.unlock();
+                    result?;
+                    notes.remove(index);
+                }
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Deletes a note from the inbox for `d`, or moves it to the file named by `target`,
+    /// leaving it be when the inbox changed in the meantime.
+    fn triage_note(&self, inbox: &str, note: &Note, target: &str) -> Result<(), String> {
+        let mut notes = self.read_notes(inbox)?;
+
+        let position = match notes.iter().position(|current| current == note) {
+            Some(position) => position,
+            None => {
+                println!(
+                    "The note changed in the meantime and was left in {}.",
+                    inbox
+                );
+                return Ok(());
+            }
+        };
+
+        let mut note = notes.remove(position);
+
+        if target != "d" {
+            self.carry_history(inbox, target, &mut note)?;
+            self.append_notes(target, &[note])?;
+        }
+
+        self.write_notes(inbox, &notes)
+    }
+
+    /// Asks on the terminal whether to go ahead with a change, if the confirmation policy
+    /// in the configuration requires it.
+    fn confirm(&self, message: &str) -> Result<(), String> {
+        if !self
+            .config
+            .confirm
+            .requires_confirmation(&self.command_type)
+        {
+            return Ok(());
+        }
+
+        let terminal = match fs::File::open("/dev/tty") {
+            Ok(t) => t,
+            Err(_) => return Err(format!("{} needs confirmation from a terminal.", message)),
+        };
+
+        print!("{}? [y/N] ", message);
+        let _ = io::stdout().flush();
+
+        let mut answer = String::new();
+
+        if let Err(e) = io::BufReader::new(terminal).read_line(&mut answer) {
+            return Err(e.to_string());
+        }
+
+        match answer.trim().to_lowercase().as_str() {
+            "y" | "yes" => Ok(()),
+            _ => Err("Cancelled.".to_string()),
+        }
+    }
+
+    fn require_git(&self) -> Result<(), String> {
+        if gitrepo::is_enabled(&self.working_directory) {
+            Ok(())
+        } else {
+            Err(format!(
+                "Version history is off, {} is not a git repository. Turn it on with: git -C {} init",
+                self.working_directory, self.working_directory
+            ))
+        }
+    }
+
+    fn note_file_path(&self, file: &str) -> String {
+        format!("{}{}", &self.working_directory, file)
+    }
+
+    /// Reads a note file, decrypting it if it is encrypted.
+    fn read_note_file(&self, file: &str) -> Result<String, String> {
+        match fs::read_to_string(self.note_file_path(file)) {
+            Ok(c) => self.decrypt_content(file, c),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    fn decrypt_content(&self, file: &str, content: String) -> Result<String, String> {
+        if !encryption::is_encrypted(&content) {
+            return Ok(content);
+        }
+
+        let cached = self
+            .key
+            .borrow()
+            .as_ref()
+            .is_some_and(|key| key.fits(&content));
+        if !cached {
+            let key = Key::for_content(self.secret(false)?, &content)?;
+            *self.key.borrow_mut() = Some(key);
+        }
+
+        match encryption::decrypt(&content, self.key.borrow().as_ref().unwrap()) {
+            Ok(plaintext) => Ok(plaintext),
+            Err(e) => Err(format!("Unable to decrypt {}, {}.", file, e)),
+        }
+    }
+
+    fn is_encrypted(&self, file: &str) -> bool {
+        fs::read_to_string(self.note_file_path(file))
+            .is_ok_and(|content| encryption::is_encrypted(&content))
+    }
+
+    /// Passphrase or key file contents used for encrypted note files, asked for only once.
+    /// `confirm` asks for a typed passphrase twice, for encrypting a file the first time.
+    fn secret(&self, confirm: bool) -> Result<&[u8], String> {
+        if let Some(secret) = self.secret.get() {
+            return Ok(secret);
+        }
+
+        let secret = if let Ok(key_file) = env::var("RN_KEY_FILE") {
+            match fs::read(&key_file) {
+                Ok(secret) => secret,
+                Err(e) => return Err(format!("Unable to read key file {}: {}", key_file, e)),
+            }
+        } else if let Ok(passphrase) = env::var("RN_PASSPHRASE") {
+            passphrase.into_bytes()
+        } else {
+            let passphrase = match rpassword::prompt_password("Passphrase: ") {
+                Ok(p) => p,
+                Err(e) => return Err(format!("Unable to read passphrase: {}", e)),
+            };
+
+            if confirm {
+                match rpassword::prompt_password("Repeat passphrase: ") {
+                    Ok(repeated) if repeated == passphrase => {}
+                    Ok(_) => return Err("Passphrases did not match.".to_string()),
+                    Err(e) => return Err(format!("Unable to read passphrase: {}", e)),
+                }
+            }
+
+            passphrase.into_bytes()
+        };
+
+        if secret.is_empty() {
+            return Err("Passphrase can not be empty.".to_string());
+        }
+
+        Ok(self.secret.get_or_init(|| secret))
+    }
+
+    /// Writes a note file, encrypting it first if `encrypted` is set.
+    fn write_note_file(&self, file: &str, content: &str, encrypted: bool) -> Result<(), String> {
+        let path = self.note_file_path(file);
+        create_parent_directory(&path)?;
+
+        let content = if encrypted {
+            if self.key.borrow().is_none() {
+                let key = Key::generate(self.secret(false)?)?;
+                *self.key.borrow_mut() = Some(key);
+            }
+
+            encryption::encrypt(content, self.key.borrow().as_ref().unwrap())?
+        } else {
+            content.to_string()
+        };
+
+        match fs::write(path, content) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Turns encryption of a note file and its note history on or off.
+    fn set_encrypted(&self, file: &str, encrypted: bool) -> Result<(), String> {
+        let history_file = revisions::history_file(file);
+
+        for file in [file, history_file.as_str()] {
+            if fs::metadata(self.note_file_path(file)).is_ok() {
+                let content = self.read_note_file(file)?;
+                self.write_note_file(file, &content, encrypted)?;
+            }
+        }
+
+        Ok(())
+    }
+
+    fn parse_notes(&self, file: &str, content: &str) -> Result<Vec<Note>, String> {
+        match notefile::parse_notes(content) {
+            Ok(notes) => Ok(notes),
+            Err(e) => Err(format!("{} is not a valid note file, {}.", file, e)),
+        }
+    }
+
+    fn read_notes(&self, file: &str) -> Result<Vec<Note>, String> {
+        let content = self.read_note_file(file)?;
+        self.parse_notes(file, &content)
+    }
+
+    /// Earlier bodies of the edited notes in a note file.
+    fn read_history(&self, file: &str) -> Result<Vec<Note>, String> {
+        let history_file = revisions::history_file(file);
+
+        if fs::metadata(self.note_file_path(&history_file)).is_err() {
+            return Ok(Vec::new());
+        }
+
+        self.read_notes(&history_file)
+    }
+
+    /// Replaces the text of a note, keeping the old text in the note history.
+    fn replace_note_text(
+        &self,
+        file: &str,
+        notes: &mut [Note],
+        line_number: usize,
+        text: &str,
+    ) -> Result<(), String> {
+        let note = &mut notes[line_number];
+
+        if note.text == text {
+            return Ok(());
+        }
+
+        let mut history = self.read_history(file)?;
+        revisions::record_revision(&mut history, note);
+        note.set_text(text);
+        note.modified = Some(Utc::now());
+
+        self.write_note_file(
+            &revisions::history_file(file),
+            &notefile::format_notes(&history),
+            self.is_encrypted(file),
+        )?;
+        self.write_notes(file, notes)
+    }
+
+    /// Copies the earlier bodies of a note moving from `from` to `to` into the history of
+    /// `to`, so that the note's history stays with it.
+    fn carry_history(&self, from: &str, to: &str, note: &mut Note) -> Result<(), String> {
+        if note.history.is_none() {
+            return Ok(());
+        }
+
+        let from_history = self.read_history(from)?;
+        let revisions = revisions::revisions(&from_history, note);
+        let mut history = self.read_history(to)?;
+        revisions::carry_over(&mut history, note, &revisions);
+
+        if revisions.is_empty() {
+            return Ok(());
+        }
+
+        self.write_note_file(
+            &revisions::history_file(to),
+            &notefile::format_notes(&history),
+            self.is_encrypted(to),
+        )
+    }
+
+    /// Replaces the contents of a note file with the given notes, creating the file if needed.
+    fn write_notes(&self, file: &str, notes: &[Note]) -> Result<(), String> {
+        self.write_note_file(
+            file,
+            &notefile::format_notes(notes),
+            self.is_encrypted(file),
+        )
+    }
+
+    /// Adds notes to the end of a note file, creating the file if needed. Notes that do not
+    /// know when they were created are taken to be created now, and notes added to a to-do
+    /// list are open items unless they are done already.
+    fn append_notes(&self, file: &str, notes: &[Note]) -> Result<(), String> {
+        let now = Utc::now();
+        // A file that can not be read is not taken for a to-do list, the notes still go in
+        let existing_notes = match fs::metadata(self.note_file_path(file)) {
+            Ok(_) => self.read_notes(file).unwrap_or_default(),
+            Err(_) => Vec::new(),
+        };
+        let to_todo_list = is_todo_list(file, &existing_notes);
+        let notes: Vec<Note> = notes
+            .iter()
+            .map(|note| Note {
+                created: note.created.or(Some(now)),
+                done: match to_todo_list {
+                    true => note.done.or(Some(false)),
+                    false => note.done,
+                },
+                ..note.clone()
+            })
+            .collect();
+        let notes = notes.as_slice();
+
+        if self.is_encrypted(file) {
+            let mut all_notes = self.read_notes(file)?;
+            all_notes.extend_from_slice(notes);
+            return self.write_notes(file, &all_notes);
+        }
+
+        let path = self.note_file_path(file);
+        create_parent_directory(&path)?;
+
+        let mut file = match OpenOptions::new().append(true).create(true).open(path) {
+            Ok(f) => f,
+            Err(e) => return Err(e.to_string()),
+        };
+
+        match file.write_all(notefile::format_notes(notes).as_bytes()) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        }
+    }
+
+    /// Lays out a note between the given prefix and suffix, wrapping it to the terminal width
+    /// and indenting the lines after the first to line up. Secrets are masked unless
+    /// `--reveal` was given.
+    fn format_note(&self, prefix: &str, note: &Note, suffix: &str) -> String {
+        let terminal = self.terminal();
+        let text = self.visible_text(note);
+        let indent_width = terminal::visible_width(prefix);
+        let indent = " ".repeat(indent_width);
+        let mut lines = text
+            .lines()
+            .flat_map(|line| terminal.wrap(line, indent_width));
+
+        let due = match note.due {
+            Some(due) if note.done != Some(true) => {
+                let text = format!("  (due {})", due.format(duedate::DATE_FORMAT));
+                if due < self.today {
+                    terminal.paint(&text, terminal::OVERDUE_COLOR)
+                } else {
+                    terminal.paint(&text, terminal::CONTEXT_COLOR)
+                }
+            }
+            _ => String::new(),
+        };
+        let mut output = format!(
+            "{}{}{}{}{}{}\n",
+            prefix,
+            lines.next().unwrap_or_default(),
+            suffix,
+            terminal.paint(&command_context(note), terminal::CONTEXT_COLOR),
+            terminal.paint(&priority_label(note), terminal::CONTEXT_COLOR),
+            due
+        );
+
+        for line in lines {
+            output.push_str(&format!("{}{}\n", indent, line));
+        }
+
+        output
+    }
+
+    /// The text of a note as it is, without its index and with secrets unmasked, so that it
+    /// can be used in `$(rn <file> <index>)`.
+    fn format_single_note(&self, file: &str, line_number: &NoteIndex) -> Result<String, String> {
+        let notes = self.read_notes(file)?;
+
+        match notes.get(line_number.resolve(notes.len())) {
+            Some(note) => Ok(format!("{}\n", note.text)),
+            None => Err("Unable to find note that user wanted to show.".to_string()),
+        }
+    }
+
+    /// Text of a note as it is shown, with secrets masked unless `--reveal` was given.
+    fn visible_text(&self, note: &Note) -> String {
+        if self.output_options.reveal {
+            note.text.clone()
+        } else {
+            self.masker.mask(note)
+        }
+    }
+
+    /// Prints a listing, through the pager when it is longer than the terminal is high.
+    fn show(&self, output: &str) -> Result<(), String> {
+        let fits = match self.terminal().height {
+            Some(height) => output.lines().count() < height,
+            None => true,
+        };
+
+        if !fits && !self.output_options.no_pager {
+            if let Some(command) = pager::pager_command(self.config.pager.as_deref()) {
+                // The output is printed instead only when the pager did not start, as it
+                // has been shown already once the pager ran
+                match pager::page(&command, output) {
+                    Ok(false) => {}
+                    Ok(true) => return Ok(()),
+                    Err(e) => {
+                        eprintln!("Warning: {}", e);
+                        return Ok(());
+                    }
+                }
+            }
+        }
+
+        print!("{}", output);
+        Ok(())
+    }
+
+    /// Colors and width of standard output, with `--color` taking precedence over the
+    /// configuration.
+    fn terminal(&self) -> Terminal {
+        *self.terminal.get_or_init(|| {
+            let choice = self
+                .output_options
+                .color
+                .or(self.config.color)
+                .unwrap_or(ColorChoice::Auto);
+
+            Terminal::detect(choice)
+        })
+    }
+
+    fn format_changes(&self, changes: &[NoteChange]) -> String {
+        if changes.is_empty() {
+            return "No differences.\n".to_string();
+        }
+
+        let mut output = String::new();
 
-                if !was_line_edited {
-                    return Err("Unable to find line that user wanted to edit.".to_string());
+        for change in changes {
+            match change {
+                NoteChange::Added(index, note) => output.push_str(&self.format_note(
+                    &format!("{:>7} {}: ", "added", index),
+                    note,
+                    "",
+                )),
+                NoteChange::Removed(index, note) => output.push_str(&self.format_note(
+                    &format!("{:>7} {}: ", "removed", index),
+                    note,
+                    "",
+                )),
+                NoteChange::Changed(old_index, old_note, new_index, new_note) => {
+                    output.push_str(&self.format_note(
+                        &format!("{:>7} {}: ", "changed", old_index),
+                        old_note,
+                        "",
+                    ));
+                    output.push_str(&self.format_note(
+                        &format!("{:>7} {}: ", "to", new_index),
+                        new_note,
+                        "",
+                    ));
                 }
+            }
+        }
 
-                let mut file = OpenOptions::new()
-                    .write(true)
-                    .truncate(true)
-                    .open(format!("{}{}", &self.working_directory, file))
-                    .unwrap();
+        output
+    }
 
-                for line in lines {
-                    file.write_all(line.as_bytes()).unwrap();
-                    file.write_all("\n".as_bytes()).unwrap();
-                }
+    /// Commit message for adding a note, which leaves out the text of notes added to
+    /// encrypted files and of notes with secrets so that the git history never holds them
+    /// in plaintext.
+    fn describe_added_note(&self, file: &str, text: &str) -> String {
+        let note = self.new_note(text);
 
-                Ok(())
+        if self.is_encrypted(file) || self.masker.mask(&note) != note.text {
+            format!("Add note to {}", file)
+        } else {
+            format!("Add note to {}: {}", file, summary(text))
+        }
+    }
+
+    /// Describes the change a command makes to the notebook, or `None` for commands that
+    /// only read. Changing commands run under the notebook lock and, when the notes
+    /// directory is a git repository, are committed with this description.
+    fn change_description(&self) -> Option<String> {
+        match &self.command_type {
+            CommandType::RemoveNoteFile(file) => Some(format!("Remove note file {}", file)),
+            CommandType::OpenNoteFileInEditor(file, _) => Some(format!("Edit {} in editor", file)),
+            CommandType::AddNoteToFile(file, note) => Some(self.describe_added_note(file, note)),
+            CommandType::AddNoteToInbox(note) => {
+                Some(self.describe_added_note(self.config.inbox_file(), note))
             }
-            CommandType::RemoveNoteFromFile(file, line_number) => {
-                let content: String = match fs::read_to_string(format!("{}{}", &self.working_directory, file)) {
-                    Ok(c) => {
-                        c
-                    }
-                    Err(e) => {
-                        return Err(e.to_string());
-                    }
-                };
+            CommandType::AddNoteToJournal(note) => {
+                Some(self.describe_added_note(&journal::journal_file(self.today), note))
+            }
+            CommandType::TriageInbox => Some(format!("Triage {}", self.config.inbox_file())),
+            CommandType::AddNotesFromStdin(file, _) => {
+                Some(format!("Add notes to {} from standard input", file))
+            }
+            CommandType::AddLastCommandToFile(file, _) => {
+                Some(format!("Add previous shell command to {}", file))
+            }
+            CommandType::EditNoteInFile(file, line_number, _)
+            | CommandType::EditNoteInEditor(file, line_number) => {
+                Some(format!("Edit note {} in {}", line_number, file))
+            }
+            CommandType::RemoveNoteFromFile(file, selector) => {
+                Some(format!("Remove {} from {}", selector, file))
+            }
+            CommandType::SetNoteDone(file, line_number, true) => {
+                Some(format!("Mark note {} in {} as done", line_number, file))
+            }
+            CommandType::SetNoteDone(file, line_number, false) => {
+                Some(format!("Mark note {} in {} as open", line_number, file))
+            }
+            CommandType::MoveNote(file, selector, position) => {
+                Some(format!("Move {} in {} to {}", selector, file, position))
+            }
+            CommandType::SortNotes(file, _) => Some(format!("Sort notes in {}", file)),
+            CommandType::PinNote(file, line_number, true) => {
+                Some(format!("Pin note {} in {}", line_number, file))
+            }
+            CommandType::PinNote(file, line_number, false) => {
+                Some(format!("Unpin note {} in {}", line_number, file))
+            }
+            CommandType::MarkNoteSecret(file, line_number, true) => {
+                Some(format!("Mark note {} in {} as secret", line_number, file))
+            }
+            CommandType::MarkNoteSecret(file, line_number, false) => {
+                Some(format!("Unmark note {} in {} as secret", line_number, file))
+            }
+            CommandType::EncryptNoteFile(file) => Some(format!("Encrypt {}", file)),
+            CommandType::DecryptNoteFile(file) => Some(format!("Decrypt {}", file)),
+            CommandType::RevertNote(file, line_number, revision_number) => Some(format!(
+                "Revert note {} in {} to revision {}",
+                line_number, file, revision_number
+            )),
+            CommandType::RestoreNoteFile(file, revision) => {
+                Some(format!("Restore {} to {}", file, revision))
+            }
+            _ => None,
+        }
+    }
+}
 
-                let mut lines: Vec<&str> = content.lines().collect();
-                let mut was_line_removed = false;
+/// Creates the directory a note file is in, such as `journal/`, when it does not exist yet.
+fn create_parent_directory(path: &str) -> Result<(), String> {
+    match Path::new(path).parent() {
+        Some(parent) => match fs::create_dir_all(parent) {
+            Ok(()) => Ok(()),
+            Err(e) => Err(e.to_string()),
+        },
+        None => Ok(()),
+    }
+}
 
-                for i in 0..lines.len() {
-                    if i == *line_number {
-                        lines.remove(i);
-                        was_line_removed = true;
-                        break;
-                    }
-                }
+/// First line of a note, shortened to fit in a commit message subject.
+fn summary(text: &str) -> String {
+    let first_line = text.lines().next().unwrap_or("");
 
-                if !was_line_removed {
-                    return Err("Unable to remove line that user wanted to edit.".to_string());
-                }
+    if first_line.chars().count() > 50 {
+        format!("{}...", first_line.chars().take(47).collect::<String>())
+    } else {
+        first_line.to_string()
+    }
+}
 
-                let mut file = OpenOptions::new()
-                    .write(true)
-                    .truncate(true)
-                    .open(format!("{}{}", &self.working_directory, file))
-                    .unwrap();
+/// Describes where and how a command captured from the shell history was run.
+fn command_context(note: &Note) -> String {
+    match (note.exit_status, &note.cwd) {
+        (Some(status), Some(cwd)) => format!("  (exit {} in {})", status, cwd),
+        (Some(status), None) => format!("  (exit {})", status),
+        (None, Some(cwd)) => format!("  (in {})", cwd),
+        (None, None) => String::new(),
+    }
+}
 
-                for line in lines {
-                    file.write_all(line.as_bytes()).unwrap();
-                    file.write_all("\n".as_bytes()).unwrap();
-                }
+/// Marks pinned notes and notes with a priority.
+fn priority_label(note: &Note) -> String {
+    match (note.pinned, note.priority) {
+        (true, Some(priority)) => format!("  (pinned, {} priority)", priority.as_str()),
+        (true, None) => "  (pinned)".to_string(),
+        (false, Some(priority)) => format!("  ({} priority)", priority.as_str()),
+        (false, None) => String::new(),
+    }
+}
 
-                Ok(())
-            }
-            CommandType::PrintHelp => {
-                println!("rn is a simple utility for taking and storing notes using the command line.");
-                println!("");
-                println!("Show help:");
-                println!("rn help");
-                println!("rn h");
-                println!("rn");
-                println!("");
-                println!("Add a new note:");
-                println!("rn <filename> <note>");
-                println!("rn <filename> add <note>");
-                println!("rn <filename> a <note>");
-                println!("");
-                println!("List all notes:");
-                println!("rn list");
-                println!("rn l");
-                println!("");
-                println!("List all entries inside a note file:");
-                println!("rn <filename> list");
-                println!("rn <filename> l");
-                println!("rn <filename>");
-                println!("");
-                println!("Remove an entry from a file:");
-                println!("rn <filename> remove <index>");
-                println!("rn <filename> r <index>");
-                println!("");
-                println!("Edit note:");
-                println!("rn <filename> edit <index> <newtext>");
-                println!("rn <filename> e <index> <newtext>");
-                println!("");
-                println!("Open note file in a text editor:");
-                println!("rn open <filename>");
-                println!("rn o <filename>");
-                println!("");
-                println!("Remove note file and all the notes inside it:");
-                println!("rn remove <filename>");
-                println!("rn r <filename>");
-                Ok(())
-            }
-            CommandType::Error(error) => Err(error.clone()),
+/// Whether the notes of a file are listed with checkboxes: once any of its notes is a to-do
+/// item, and from the start for the file called `todo`.
+fn is_todo_list(file: &str, notes: &[Note]) -> bool {
+    file == TODO_FILE || notes.iter().any(|note| note.done.is_some())
+}
+
+/// Where a note goes in a listing: pinned notes first, then by priority, with notes without
+/// a priority between medium and low ones.
+fn listing_rank(note: &Note) -> u8 {
+    match (note.pinned, note.priority) {
+        (true, _) => 0,
+        (false, Some(Priority::High)) => 1,
+        (false, Some(Priority::Medium)) => 2,
+        (false, None) => 3,
+        (false, Some(Priority::Low)) => 4,
+    }
+}
+
+/// Turns text read from standard input into notes: one note per non-empty line, or the
+/// whole input as a single note when `multiline` is set.
+fn notes_from_input(input: &str, multiline: bool) -> Vec<Note> {
+    if multiline {
+        let text = input.trim_end_matches(['\n', '\r']);
+
+        if text.trim().is_empty() {
+            return Vec::new();
         }
+
+        return vec![Note::new(text)];
     }
+
+    input
+        .lines()
+        .filter(|line| !line.trim().is_empty())
+        .map(Note::new)
+        .collect()
 }
 
-#[allow(unused_variables)]
+#[allow(unused_variables, clippy::needless_return)]
 mod rntool_tests {
-    use crate::parsearguments::{rn_get_command_type, CommandType};
+    #[allow(unused_imports)]
+    use super::{listing_rank, notes_from_input};
+    #[allow(unused_imports)]
+    use crate::gitrepo;
+    #[allow(unused_imports)]
+    use crate::notefile::{self, Note, Priority};
+    #[allow(unused_imports)]
+    use crate::parsearguments::{rn_get_command_type, rn_get_note_options, CommandType};
+    #[allow(unused_imports)]
+    use crate::revisions;
+    #[allow(unused_imports)]
+    use crate::selection::NoteIndex;
     use crate::RnTool;
+    #[allow(unused_imports)]
+    use chrono::{Local, NaiveDate};
+    #[allow(unused_imports)]
+    use std::{env, fs, process::Command};
 
     #[test]
     fn test_help_with_no_params() -> Result<(), String> {
@@ -376,11 +1868,718 @@ mod rntool_tests {
         }
     }
 
+    #[test]
+    fn test_notes_from_input() {
+        assert_eq!(
+            notes_from_input("first\n\nsecond\r\n", false),
+            vec![Note::new("first"), Note::new("second")]
+        );
+        assert_eq!(
+            notes_from_input("first\nsecond\n\n", true),
+            vec![Note::new("first\nsecond")]
+        );
+        assert_eq!(notes_from_input("\n\n", true), vec![]);
+    }
+
+    #[test]
+    fn test_edit_note_to_multiple_lines() -> Result<(), String> {
+        qtest("multilinetest", "a", "this is my note", "").unwrap();
+        qtest("multilinetest", "e", "0", "first line\nsecond line")?;
+        qtest("multilinetest", "l", "", "")
+    }
+
+    #[test]
+    fn test_git_history_and_restore() -> Result<(), String> {
+        let working_directory = "./rn-tests-data/gittest/";
+        init_git_repository(working_directory);
+
+        let run = |args: Vec<&str>| {
+            let mut full_args = vec!["rn".to_string()];
+            full_args.extend(args.iter().map(|arg| arg.to_string()));
+            RnTool::new(
+                working_directory.to_string(),
+                rn_get_command_type(full_args),
+            )
+            .execute_command()
+        };
+
+        run(vec!["notes", "a", "first note"])?;
+        run(vec!["notes", "e", "0", "second note"])?;
+
+        let log = gitrepo::log(working_directory, Some("notes"))?;
+        assert_eq!(log.lines().count(), 2);
+        assert!(log
+            .lines()
+            .next()
+            .unwrap()
+            .ends_with("Edit note 0 in notes"));
+
+        run(vec!["diff", "notes", "--since", "HEAD~1"])?;
+        run(vec!["restore", "notes", "HEAD~1"])?;
+
+        let content = read_note_file(&format!("{}notes", working_directory));
+        assert_eq!(content, "first note\n");
+        assert_eq!(gitrepo::log(working_directory, None)?.lines().count(), 3);
+
+        Ok(())
+    }
+
+    #[test]
+    fn test_git_history_leaves_out_secrets() -> Result<(), String> {
+        let working_directory = "./rn-tests-data/gitsecrettest/";
+        init_git_repository(working_directory);
+        let run = |args| encrypted_tool_in(working_directory, args).execute_command();
+
+        run(vec!["vault", "a", "first"])?;
+        run(vec!["vault", "--encrypt"])?;
+        run(vec!["vault", "a", "mysql -h db"])?;
+        run(vec!["notes", "a", "password=hunter2"])?;
+        run(vec!["notes", "a", "plain note"])?;
+
+        let log = gitrepo::log(working_directory, None)?;
+        assert!(!log.contains("mysql"));
+        assert!(!log.contains("hunter2"));
+        assert!(log.contains("Add note to vault\n"));
+        assert!(log.contains("Add note to notes: plain note"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_note_history_and_revert() -> Result<(), String> {
+        remove_note_file("notehistorytest");
+
+        qtest("notehistorytest", "a", "first text", "")?;
+        qtest("notehistorytest", "a", "another note", "")?;
+        qtest("notehistorytest", "e", "1", "second text")?;
+        qtest("notehistorytest", "e", "1", "third text")?;
+        qtest("notehistorytest", "r", "0", "")?;
+        qtest("notehistorytest", "history", "0", "")?;
+        qtest("notehistorytest", "revert", "0", "0")?;
+
+        let content = read_note_file("./rn-tests-data/notehistorytest");
+        assert_eq!(content, "%{history=0} another note\n");
+
+        let history = fs::read_to_string("./rn-tests-data/.history/notehistorytest").unwrap();
+        let texts: Vec<&str> = history
+            .lines()
+            .map(|line| line.split_once("} ").unwrap().1)
+            .collect();
+        assert_eq!(texts, vec!["another note", "second text", "third text"]);
+
+        assert!(qtest("notehistorytest", "revert", "0", "5").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_diff_notefiles() -> Result<(), String> {
+        qtest("difftest1", "a", "same note", "")?;
+        qtest("difftest2", "a", "same note", "")?;
+        qtest("difftest2", "a", "new note", "")?;
+        qtest("diff", "difftest1", "difftest2", "")?;
+        assert!(qtest("diff", "difftest1", "difftest_doesnotexist", "").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_encrypted_notefile() -> Result<(), String> {
+        let path = "./rn-tests-data/encryptiontest";
+        remove_note_file("encryptiontest");
+        let run = |args| encrypted_tool_in("./rn-tests-data/", args).execute_command();
+
+        run(vec!["encryptiontest", "a", "secret note"])?;
+        run(vec!["encryptiontest", "--encrypt"])?;
+        assert!(run(vec!["encryptiontest", "--encrypt"]).is_err());
+
+        run(vec!["encryptiontest", "a", "another secret"])?;
+        run(vec!["encryptiontest", "e", "0", "edited secret"])?;
+        run(vec!["encryptiontest", "r", "1"])?;
+        run(vec!["encryptiontest", "l"])?;
+
+        for file in [path, "./rn-tests-data/.history/encryptiontest"] {
+            let content = fs::read_to_string(file).unwrap();
+            assert!(content.starts_with("rn-encrypted-v1\n"));
+            assert!(!content.contains("secret"));
+        }
+
+        // The editor only ever sees a copy kept in memory
+        let mut tool = encrypted_tool_in("./rn-tests-data/", vec!["open", "encryptiontest"]);
+        tool.config.editor = Some("sed -i s/edited/reopened/".to_string());
+        tool.execute_command()?;
+        assert!(fs::read_to_string(path)
+            .unwrap()
+            .starts_with("rn-encrypted-v1\n"));
+
+        run(vec!["encryptiontest", "--decrypt"])?;
+        assert_eq!(read_note_file(path), "%{history=0} reopened secret\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_edit_note_in_editor() -> Result<(), String> {
+        let working_directory = "./rn-tests-data/editortest/";
+        let path = format!("{}notes", working_directory);
+        let _ = fs::remove_dir_all(working_directory);
+        let edit = |editor: &str, args| {
+            let mut tool = encrypted_tool_in(working_directory, args);
+            tool.config.editor = Some(editor.to_string());
+            tool.execute_command()
+        };
+
+        tool_in(working_directory, vec!["notes", "a", "first draft"]).execute_command()?;
+        tool_in(working_directory, vec!["notes", "a", "second note"]).execute_command()?;
+        edit("sed -i s/draft/version/", vec!["notes", "e", "0"])?;
+        assert_eq!(
+            read_note_file(&path),
+            "%{history=0} first version\nsecond note\n"
+        );
+
+        // An editor leaving the note empty cancels the edit
+        edit("truncate -s 0", vec!["notes", "e", "-1"])?;
+        assert_eq!(
+            read_note_file(&path),
+            "%{history=0} first version\nsecond note\n"
+        );
+
+        // Notes of encrypted files are edited through a copy kept in memory
+        edit("true", vec!["notes", "--encrypt"])?;
+        edit("sed -i s/second/last/", vec!["notes", "e", "1"])?;
+        assert!(fs::read_to_string(&path)
+            .unwrap()
+            .starts_with("rn-encrypted-v1\n"));
+        edit("true", vec!["notes", "--decrypt"])?;
+        assert_eq!(
+            read_note_file(&path),
+            "%{history=0} first version\n%{history=1} last note\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_open_at_note() -> Result<(), String> {
+        let working_directory = "./rn-tests-data/opentest/";
+        let script = "./rn-tests-data/opentest-editor";
+        let _ = fs::remove_dir_all(working_directory);
+        fs::create_dir_all(working_directory).unwrap();
+        fs::write(script, "echo \"$@\" > ./rn-tests-data/opentest/arguments\n").unwrap();
+        fs::write(
+            format!("{}notes", working_directory),
+            "first\nsecond\nthird\n",
+        )
+        .unwrap();
+
+        for (at, line) in [("0", "+1"), ("-1", "+3"), ("last", "+3")] {
+            let mut tool = tool_in(working_directory, vec!["open", "notes", "--at", at]);
+            tool.config.editor = Some(format!("sh {}", script));
+            tool.execute_command()?;
+            assert_eq!(
+                fs::read_to_string(format!("{}arguments", working_directory)).unwrap(),
+                format!("{} ./rn-tests-data/opentest/notes\n", line)
+            );
+        }
+
+        let tool = tool_in(working_directory, vec!["open", "notes", "--at", "-4"]);
+        assert!(tool.execute_command().is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_notebook_is_not_locked_during_edits() -> Result<(), String> {
+        let working_directory = "./rn-tests-data/editlocktest/";
+        let script = "./rn-tests-data/editlocktest-editor";
+        let _ = fs::remove_dir_all(working_directory);
+        let edit = |script_text: &str| {
+            fs::write(script, script_text).unwrap();
+            let mut tool = tool_in(working_directory, vec!["notes", "e", "0"]);
+            tool.config.editor = Some(format!("sh {}", script));
+            tool.execute_command()
+        };
+
+        tool_in(working_directory, vec!["notes", "a", "first draft"]).execute_command()?;
+
+        // Another process takes the lock and writes while the editor is open
+        edit(
+            "flock -w 1 ./rn-tests-data/editlocktest/.rn.lock \
+                 sh -c 'echo meanwhile >> ./rn-tests-data/editlocktest/other' || exit 1\n\
+             sed -i s/draft/version/ \"$1\"\n",
+        )?;
+        assert_eq!(
+            read_note_file(&format!("{}other", working_directory)),
+            "meanwhile\n"
+        );
+        assert_eq!(
+            read_note_file(&format!("{}notes", working_directory)),
+            "%{history=0} first version\n"
+        );
+
+        // An edit of a note that changed in the meantime is left out
+        let result = edit(
+            "sed -i s/version/rewrite/ ./rn-tests-data/editlocktest/notes\n\
+             sed -i s/version/edit/ \"$1\"\n",
+        );
+        assert!(result
+            .unwrap_err()
+            .contains("changed while it was being edited"));
+        assert_eq!(
+            read_note_file(&format!("{}notes", working_directory)),
+            "%{history=0} first rewrite\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_secret_note() -> Result<(), String> {
+        let path = "./rn-tests-data/secrettest";
+        remove_note_file("secrettest");
+
+        qtest("secrettest", "a", "password=hunter2", "")?;
+        qtest("secrettest", "secret", "0", "")?;
+        assert_eq!(read_note_file(path), "%{secret} password=hunter2\n");
+        qtest("secrettest", "l", "", "")?;
+
+        qtest("secrettest", "unsecret", "0", "")?;
+        assert_eq!(read_note_file(path), "password=hunter2\n");
+        assert!(qtest("secrettest", "secret", "1", "").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_todo_notes() -> Result<(), String> {
+        let path = "./rn-tests-data/todotest";
+        remove_note_file("todotest");
+
+        qtest("todotest", "a", "renew certificates", "")?;
+        qtest("todotest", "a", "rotate ssh keys", "")?;
+        qtest("todotest", "done", "1", "")?;
+        qtest("todotest", "l", "--open", "")?;
+
+        let content = read_note_file(path);
+        assert!(content.starts_with("renew certificates\n%{done,completed="));
+        assert!(content.ends_with("Z} rotate ssh keys\n"));
+
+        qtest("todotest", "undo", "1", "")?;
+        assert_eq!(
+            read_note_file(path),
+            "renew certificates\n%{todo} rotate ssh keys\n"
+        );
+
+        assert!(qtest("todotest", "done", "2", "").is_err());
+
+        qtest("todotest", "a", "check backups", "")?;
+        assert!(read_note_file(path).ends_with("\n%{todo} check backups\n"));
+        Ok(())
+    }
+
+    #[test]
+    fn test_todo_file_starts_as_todo_list() -> Result<(), String> {
+        let working_directory = "./rn-tests-data/todofiletest/";
+        let _ = fs::remove_dir_all(working_directory);
+
+        tool_in(working_directory, vec!["todo", "a", "renew certificates"]).execute_command()?;
+        tool_in(working_directory, vec!["notes", "a", "plain note"]).execute_command()?;
+
+        assert_eq!(
+            read_note_file(&format!("{}todo", working_directory)),
+            "%{todo} renew certificates\n"
+        );
+        assert_eq!(
+            read_note_file(&format!("{}notes", working_directory)),
+            "plain note\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_reorder_notes() -> Result<(), String> {
+        let path = "./rn-tests-data/reordertest";
+        remove_note_file("reordertest");
+
+        qtest("reordertest", "a", "charlie", "")?;
+        qtest("reordertest", "a", "alpha", "")?;
+        qtest("reordertest", "a", "bravo", "")?;
+
+        qtest("reordertest", "mv", "2", "0")?;
+        assert_eq!(read_note_file(path), "bravo\ncharlie\nalpha\n");
+        qtest("reordertest", "up", "2", "")?;
+        assert_eq!(read_note_file(path), "bravo\nalpha\ncharlie\n");
+        qtest("reordertest", "down", "0", "")?;
+        assert_eq!(read_note_file(path), "alpha\nbravo\ncharlie\n");
+        assert!(qtest("reordertest", "down", "2", "").is_err());
+        assert!(qtest("reordertest", "mv", "3", "0").is_err());
+
+        fs::write(
+            path,
+            "%{created=2020-01-02T00:00:00Z} alpha\n\
+             %{created=2020-01-03T00:00:00Z} bravo\n\
+             %{created=2020-01-01T00:00:00Z} charlie\n",
+        )
+        .unwrap();
+        qtest("reordertest", "sort", "--by=created", "")?;
+        assert_eq!(read_note_file(path), "charlie\nalpha\nbravo\n");
+        qtest("reordertest", "e", "1", "alpha edited")?;
+        qtest("reordertest", "sort", "--by=modified", "")?;
+        assert!(read_note_file(path).starts_with("%{history=0} alpha edited\n"));
+        qtest("reordertest", "sort", "", "")?;
+        assert_eq!(
+            read_note_file(path),
+            "%{history=0} alpha edited\nbravo\ncharlie\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_recent_notes() -> Result<(), String> {
+        let working_directory = "./rn-tests-data/recenttest/";
+        let _ = fs::remove_dir_all(working_directory);
+        fs::create_dir_all(working_directory).unwrap();
+        fs::write(
+            format!("{}servers", working_directory),
+            "%{created=2099-01-01T00:00:00Z} newest\n\
+             %{created=2098-01-01T00:00:00Z,modified=2099-06-01T00:00:00Z} edited\n",
+        )
+        .unwrap();
+        fs::write(
+            format!("{}todo", working_directory),
+            "%{created=2097-01-01T00:00:00Z} older\nno time\n",
+        )
+        .unwrap();
+
+        let recent = tool_in(working_directory, vec!["recent"]).recent_notes(3)?;
+        let recent: Vec<(&str, usize, &str)> = recent
+            .iter()
+            .map(|(file, index, note)| (file.as_str(), *index, note.text.as_str()))
+            .collect();
+        assert_eq!(
+            recent,
+            vec![
+                ("servers", 1, "edited"),
+                ("servers", 0, "newest"),
+                ("todo", 0, "older")
+            ]
+        );
+
+        tool_in(working_directory, vec!["recent", "-n", "3"]).execute_command()?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_journal() -> Result<(), String> {
+        let working_directory = "./rn-tests-data/journaltest/";
+        let _ = fs::remove_dir_all(working_directory);
+        fs::create_dir_all(format!("{}journal", working_directory)).unwrap();
+        fs::write(
+            format!("{}journal/2026-10-18", working_directory),
+            "shipped the fix\npassword=hunter2\n",
+        )
+        .unwrap();
+
+        let tool = |args: Vec<&str>| {
+            let mut tool = tool_in(working_directory, args);
+            tool.today = NaiveDate::from_ymd_opt(2026, 10, 19).unwrap();
+            tool
+        };
+
+        tool(vec!["journal", "standup at ten"]).execute_command()?;
+        tool(vec!["journal", "review", "the release"]).execute_command()?;
+        assert_eq!(
+            read_note_file(&format!("{}journal/2026-10-19", working_directory)),
+            "standup at ten\nreview the release\n"
+        );
+
+        let journal = tool(vec!["journal", "cat", "yesterday"]);
+        let CommandType::PrintJournal(period) = journal.command_type else {
+            panic!("Expected rn journal cat to print the journal");
+        };
+        assert_eq!(
+            journal.format_journal(&period)?,
+            "2026-10-18\nshipped the fix\npassword=****\n\n\
+             2026-10-19\nstandup at ten\nreview the release\n"
+        );
+
+        tool(vec!["journal", "l", "last-week"]).execute_command()?;
+        assert!(tool(vec!["journal", "l", "2026-10-17"])
+            .execute_command()
+            .is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_journal_file_in_the_way() -> Result<(), String> {
+        let working_directory = "./rn-tests-data/journalconflicttest/";
+        let _ = fs::remove_dir_all(working_directory);
+        fs::create_dir_all(working_directory).unwrap();
+        fs::write(format!("{}journal", working_directory), "old note\n").unwrap();
+
+        let result = tool_in(working_directory, vec!["journal", "standup"]).execute_command();
+        assert!(result.unwrap_err().contains("rename it"));
+        assert!(tool_in(working_directory, vec!["journal", "l"])
+            .execute_command()
+            .is_err());
+        assert_eq!(
+            read_note_file(&format!("{}journal", working_directory)),
+            "old note\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn test_show_note() -> Result<(), String> {
+        remove_note_file("shownotetest");
+
+        qtest("shownotetest", "a", "ssh -J bastion db", "")?;
+        qtest("shownotetest", "a", "password=hunter2\nsecond line", "")?;
+        qtest("shownotetest", "show", "0", "")?;
+
+        let tool = get_tool(fake_args("shownotetest", "-1", "", ""));
+        assert_eq!(
+            tool.format_single_note("shownotetest", &NoteIndex::FromStart(0))?,
+            "ssh -J bastion db\n"
+        );
+        assert_eq!(
+            tool.format_single_note("shownotetest", &NoteIndex::FromEnd(1))?,
+            "password=hunter2\nsecond line\n"
+        );
+        assert!(qtest("shownotetest", "show", "2", "").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_indices_from_end() -> Result<(), String> {
+        let path = "./rn-tests-data/lastindextest";
+        remove_note_file("lastindextest");
+
+        for note in ["first", "second", "third"] {
+            qtest("lastindextest", "a", note, "")?;
+        }
+
+        qtest("lastindextest", "e", "last", "third edited")?;
+        qtest("lastindextest", "r", "-2", "")?;
+        qtest("lastindextest", "up", "-1", "")?;
+        assert_eq!(read_note_file(path), "%{history=0} third edited\nfirst\n");
+        assert!(qtest("lastindextest", "r", "-3", "").is_err());
+        qtest("lastindextest", "r", "-2--1", "")?;
+        assert_eq!(read_note_file(path), "");
+        Ok(())
+    }
+
+    #[test]
+    fn test_select_several_notes() -> Result<(), String> {
+        let path = "./rn-tests-data/selecttest";
+        let _ = fs::remove_file(path);
+
+        for note in [
+            "zero",
+            "one",
+            "rm /tmp/a",
+            "three",
+            "four",
+            "rm /tmp/b",
+            "six",
+        ] {
+            qtest("selecttest", "a", note, "")?;
+        }
+
+        qtest("selecttest", "r", "1,3-4", "")?;
+        assert_eq!(read_note_file(path), "zero\nrm /tmp/a\nrm /tmp/b\nsix\n");
+        qtest("selecttest", "mv", "--match=tmp", "2")?;
+        assert_eq!(read_note_file(path), "zero\nsix\nrm /tmp/a\nrm /tmp/b\n");
+        assert!(qtest("selecttest", "mv", "0,1", "3").is_err());
+        qtest("selecttest", "r", "--match", "tmp")?;
+        assert_eq!(read_note_file(path), "zero\nsix\n");
+        assert!(qtest("selecttest", "r", "0,2", "").is_err());
+        assert!(qtest("selecttest", "r", "--match", "tmp").is_err());
+        assert_eq!(read_note_file(path), "zero\nsix\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_pin_note() -> Result<(), String> {
+        let path = "./rn-tests-data/pintest";
+        let _ = fs::remove_file(path);
+
+        qtest("pintest", "a", "git log --oneline", "")?;
+        qtest("pintest", "a", "ssh db", "")?;
+        qtest("pintest", "pin", "1", "")?;
+        assert_eq!(
+            read_note_file(path),
+            "git log --oneline\n%{pinned} ssh db\n"
+        );
+
+        qtest("pintest", "unpin", "1", "")?;
+        assert_eq!(read_note_file(path), "git log --oneline\nssh db\n");
+        assert!(qtest("pintest", "pin", "2", "").is_err());
+        Ok(())
+    }
+
+    #[test]
+    fn test_listing_rank() {
+        let note = |pinned, priority| Note {
+            pinned,
+            priority,
+            ..Note::new("note")
+        };
+        let mut notes = vec![
+            note(false, Some(Priority::Low)),
+            note(false, None),
+            note(false, Some(Priority::High)),
+            note(true, Some(Priority::Low)),
+        ];
+        notes.sort_by_key(listing_rank);
+
+        assert_eq!(
+            notes,
+            vec![
+                note(true, Some(Priority::Low)),
+                note(false, Some(Priority::High)),
+                note(false, None),
+                note(false, Some(Priority::Low)),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_due_notes() -> Result<(), String> {
+        let working_directory = "./rn-tests-data/duetest/";
+        let today = Local::now().date_naive();
+        let _ = fs::remove_dir_all(working_directory);
+        fs::create_dir_all(working_directory).unwrap();
+        fs::write(format!("{}broken", working_directory), "%{due=someday} x\n").unwrap();
+        fs::write(format!("{}binary", working_directory), [0xff, 0xfe, 0x00]).unwrap();
+
+        tool_in(working_directory, vec!["certs", "a", "renew certificates"]).execute_command()?;
+        tool_in(working_directory, vec!["certs", "a", "rotate ssh keys"]).execute_command()?;
+
+        let note_options =
+            rn_get_note_options(&mut vec!["rn".into(), "--due=2000-01-01".into()], today)?;
+        tool_in(working_directory, vec!["certs", "e", "0"])
+            .with_note_options(note_options)
+            .execute_command()?;
+
+        assert_eq!(
+            read_note_file(&format!("{}certs", working_directory)),
+            "%{due=2000-01-01} renew certificates\nrotate ssh keys\n"
+        );
+
+        let tool = tool_in(working_directory, vec!["remind"]);
+        tool.execute_command()?;
+        assert_eq!(tool.exit_status(), 1);
+
+        tool_in(working_directory, vec!["certs", "done", "0"]).execute_command()?;
+        let tool = tool_in(working_directory, vec!["remind"]);
+        tool.execute_command()?;
+        assert_eq!(tool.exit_status(), 0);
+        assert_eq!(tool.due_notes()?.len(), 0);
+        Ok(())
+    }
+
+    #[test]
+    fn test_legacy_lines_starting_like_a_header() -> Result<(), String> {
+        let path = "./rn-tests-data/curltest";
+        remove_note_file("curltest");
+        fs::create_dir_all("./rn-tests-data").unwrap();
+        fs::write(path, "%{http_code} after curl -w\nsecond\n").unwrap();
+
+        qtest("curltest", "l", "", "")?;
+        qtest("curltest", "r", "1", "")?;
+        assert_eq!(read_note_file(path), "%{} %{http_code} after curl -w\n");
+        Ok(())
+    }
+
+    #[test]
+    fn test_copy_invalid_line() {
+        qtest("copytest", "a", "first note", "").unwrap();
+        assert!(qtest("copytest", "copy", "1", "").is_err());
+    }
+
+    #[test]
+    fn test_inbox() -> Result<(), String> {
+        let path = "./rn-tests-data/inbox";
+        remove_note_file("inbox");
+        remove_note_file("inboxtest_errands");
+        fs::create_dir_all("./rn-tests-data/.history").unwrap();
+        fs::write(
+            "./rn-tests-data/.history/inboxtest_errands",
+            "%{history=0} unrelated body\n",
+        )
+        .unwrap();
+
+        qtest("-", "call the bnak", "", "")?;
+        qtest("inbox", "e", "0", "call the bank")?;
+        qtest("add", "buy", "milk", "")?;
+        qtest("add", "old idea", "", "")?;
+        qtest("add", "keep this", "", "")?;
+        assert_eq!(
+            read_note_file(path),
+            "%{history=0} call the bank\nbuy milk\nold idea\nkeep this\n"
+        );
+
+        let tool = get_tool(fake_args("inbox", "triage", "", ""));
+        tool.triage_inbox(&mut "inboxtest_errands\n\nd\nq\n".as_bytes())?;
+
+        assert_eq!(read_note_file(path), "buy milk\nkeep this\n");
+        assert_eq!(
+            read_note_file("./rn-tests-data/inboxtest_errands"),
+            "%{history=1} call the bank\n"
+        );
+
+        let errands = tool.read_notes("inboxtest_errands")?;
+        let history = tool.read_history("inboxtest_errands")?;
+        let revisions = revisions::revisions(&history, &errands[0]);
+        assert_eq!(revisions.len(), 1);
+        assert_eq!(revisions[0].text, "call the bnak");
+        Ok(())
+    }
+
+    #[test]
+    fn test_history_without_git() {
+        assert!(qtest("log", "", "", "").is_err());
+    }
+
     #[allow(dead_code)]
     fn qtest(arg1: &str, arg2: &str, arg3: &str, arg4: &str) -> Result<(), String> {
         get_tool(fake_args(arg1, arg2, arg3, arg4)).execute_command()
     }
 
+    /// Removes what earlier runs of a test left of a note file and its history.
+    #[allow(dead_code)]
+    fn remove_note_file(file: &str) {
+        let _ = fs::remove_file(format!("./rn-tests-data/{}", file));
+        let _ = fs::remove_file(format!("./rn-tests-data/.history/{}", file));
+    }
+
+    /// Contents of a note file without the times notes were added and edited, which differ
+    /// from run to run.
+    #[allow(dead_code)]
+    fn read_note_file(path: &str) -> String {
+        let mut notes = notefile::parse_notes(&fs::read_to_string(path).unwrap()).unwrap();
+
+        for note in &mut notes {
+            note.created = None;
+            note.modified = None;
+        }
+
+        notefile::format_notes(&notes)
+    }
+
+    /// Starts an empty notes directory that is a git repository.
+    #[allow(dead_code)]
+    fn init_git_repository(working_directory: &str) {
+        let _ = fs::remove_dir_all(working_directory);
+        fs::create_dir_all(working_directory).unwrap();
+
+        for args in [
+            vec!["init", "--quiet"],
+            vec!["config", "user.name", "rn tests"],
+            vec!["config", "user.email", "rn-tests@localhost"],
+        ] {
+            Command::new("git")
+                .arg("-C")
+                .arg(working_directory)
+                .args(args)
+                .status()
+                .unwrap();
+        }
+    }
+
     #[allow(dead_code)]
     fn fake_args(arg1: &str, arg2: &str, arg3: &str, arg4: &str) -> Vec<String> {
         vec![
@@ -392,6 +2591,25 @@ mod rntool_tests {
         ]
     }
 
+    /// A tool for the command in `args` working on its own notes directory.
+    #[allow(dead_code)]
+    fn tool_in(working_directory: &str, args: Vec<&str>) -> RnTool {
+        let mut full_args = vec!["rn".to_string()];
+        full_args.extend(args.iter().map(|arg| arg.to_string()));
+        RnTool::new(
+            working_directory.to_string(),
+            rn_get_command_type(full_args),
+        )
+    }
+
+    /// Tool that opens encrypted note files with the test passphrase instead of asking.
+    #[allow(dead_code)]
+    fn encrypted_tool_in(working_directory: &str, args: Vec<&str>) -> RnTool {
+        let tool = tool_in(working_directory, args);
+        let _ = tool.secret.set(b"rn tests passphrase".to_vec());
+        tool
+    }
+
     #[allow(dead_code)]
     fn get_tool(args: Vec<String>) -> RnTool {
         let command_type = rn_get_command_type(args);
@@ -401,9 +2619,7 @@ mod rntool_tests {
             CommandType::Error(x) => {
                 panic!("Illegal Argument(s): {}", x)
             }
-            ctype => {
-                RnTool::new(working_directory, ctype)
-            }
+            ctype => RnTool::new(working_directory, ctype),
         }
     }
 }
@@ -0,0 +1,272 @@
+use std::fmt;
+use std::ops::RangeInclusive;
+
+use crate::notefile::Note;
+
+const LIST_SEPARATOR: char = ',';
+const RANGE_SEPARATOR: char = '-';
+const LAST: &str = "last";
+
+/// A note given by its index, or counted back from the end with `-1` or `last` for the last
+/// note, `-2` for the one before it and so on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum NoteIndex {
+    FromStart(usize),
+    FromEnd(usize),
+}
+
+impl NoteIndex {
+    pub fn parse(value: &str) -> Result<NoteIndex, String> {
+        let index = if value == LAST {
+            Some(NoteIndex::FromEnd(1))
+        } else if let Some(back) = value.strip_prefix('-') {
+            back.parse::<usize>()
+                .ok()
+                .filter(|&back| back > 0)
+                .map(NoteIndex::FromEnd)
+        } else {
+            value.parse::<usize>().ok().map(NoteIndex::FromStart)
+        };
+
+        index.ok_or_else(|| format!("Unable to parse string to id: {}", value))
+    }
+
+    /// Position of the note in a file of `count` notes. Counting back past the first note
+    /// gives `count`, which like any other index past the end finds no note.
+    pub fn resolve(&self, count: usize) -> usize {
+        match *self {
+            NoteIndex::FromStart(index) => index,
+            NoteIndex::FromEnd(back) => count.checked_sub(back).unwrap_or(count),
+        }
+    }
+}
+
+impl fmt::Display for NoteIndex {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NoteIndex::FromStart(index) => write!(f, "{}", index),
+            NoteIndex::FromEnd(back) => write!(f, "-{}", back),
+        }
+    }
+}
+
+/// The notes of a file that a command such as `rn <file> r` is about.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum NoteSelector {
+    /// Indices and ranges given as `2,5,7-9` or `-3--1`.
+    Ids(Vec<RangeInclusive<NoteIndex>>),
+    /// Notes containing the text given with `--match`.
+    Matching(String),
+}
+
+impl NoteSelector {
+    pub fn single(index: NoteIndex) -> NoteSelector {
+        NoteSelector::Ids(vec![index..=index])
+    }
+
+    pub fn parse(value: &str) -> Result<NoteSelector, String> {
+        let mut ids = Vec::new();
+
+        for part in value.split(LIST_SEPARATOR) {
+            let part = part.trim();
+            // A range separator right at the start is the sign of a negative index
+            let separator = part
+                .char_indices()
+                .skip(1)
+                .find(|&(_, c)| c == RANGE_SEPARATOR)
+                .map(|(position, _)| position);
+
+            let (first, last) = match separator {
+                Some(position) => (&part[..position], &part[position + 1..]),
+                None => (part, part),
+            };
+
+            ids.push(NoteIndex::parse(first)?..=NoteIndex::parse(last)?);
+        }
+
+        Ok(NoteSelector::Ids(ids))
+    }
+
+    /// Indices of the selected notes in order, each once. They all refer to the notes as
+    /// they are before a command changes any of them.
+    pub fn resolve(&self, notes: &[Note]) -> Result<Vec<usize>, String> {
+        let mut indices: Vec<usize> = match self {
+            NoteSelector::Ids(ids) => {
+                let mut indices = Vec::new();
+
+                for range in ids {
+                    let first = find_note(range.start(), notes)?;
+                    let last = find_note(range.end(), notes)?;
+
+                    if first > last {
+                        return Err(format!(
+                            "Range {}-{} goes backwards.",
+                            range.start(),
+                            range.end()
+                        ));
+                    }
+
+                    indices.extend(first..=last);
+                }
+
+                indices
+            }
+            NoteSelector::Matching(text) => notes
+                .iter()
+                .enumerate()
+                .filter(|(_, note)| note.text.contains(text.as_str()))
+                .map(|(index, _)| index)
+                .collect(),
+        };
+
+        indices.sort_unstable();
+        indices.dedup();
+
+        if indices.is_empty() {
+            return Err(format!("No note matched {}.", self));
+        }
+
+        Ok(indices)
+    }
+}
+
+/// Position of the note `id` stands for, or an error when there is no such note.
+fn find_note(id: &NoteIndex, notes: &[Note]) -> Result<usize, String> {
+    match id.resolve(notes.len()) {
+        index if index < notes.len() => Ok(index),
+        _ => Err(format!(
+            "Unable to find note {}, the file has {} notes.",
+            id,
+            notes.len()
+        )),
+    }
+}
+
+impl fmt::Display for NoteSelector {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            NoteSelector::Ids(ids) => match ids.as_slice() {
+                [range] if range.start() == range.end() => write!(f, "note {}", range.start()),
+                _ => {
+                    let ids: Vec<String> = ids
+                        .iter()
+                        .map(|range| match range.start() == range.end() {
+                            true => range.start().to_string(),
+                            false => format!("{}-{}", range.start(), range.end()),
+                        })
+                        .collect();
+                    write!(f, "notes {}", ids.join(", "))
+                }
+            },
+            NoteSelector::Matching(text) => write!(f, "notes matching '{}'", text),
+        }
+    }
+}
+
+#[allow(unused_variables)]
+mod selection_tests {
+    #[allow(unused_imports)]
+    use crate::notefile::Note;
+    #[allow(unused_imports)]
+    use crate::selection::{NoteIndex, NoteSelector};
+
+    #[allow(dead_code)]
+    fn notes(texts: &[&str]) -> Vec<Note> {
+        texts.iter().map(|text| Note::new(text)).collect()
+    }
+
+    #[allow(dead_code)]
+    fn ids(ranges: &[(NoteIndex, NoteIndex)]) -> NoteSelector {
+        NoteSelector::Ids(ranges.iter().map(|&(first, last)| first..=last).collect())
+    }
+
+    #[test]
+    fn test_parse_index() {
+        assert_eq!(NoteIndex::parse("5"), Ok(NoteIndex::FromStart(5)));
+        assert_eq!(NoteIndex::parse("-1"), Ok(NoteIndex::FromEnd(1)));
+        assert_eq!(NoteIndex::parse("last"), Ok(NoteIndex::FromEnd(1)));
+
+        for value in ["", "-", "-0", "--1", "x", "first"] {
+            assert!(NoteIndex::parse(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_resolve_index() {
+        assert_eq!(NoteIndex::FromStart(2).resolve(4), 2);
+        assert_eq!(NoteIndex::FromEnd(1).resolve(4), 3);
+        assert_eq!(NoteIndex::FromEnd(4).resolve(4), 0);
+        assert_eq!(NoteIndex::FromEnd(5).resolve(4), 4);
+    }
+
+    #[test]
+    fn test_parse_ids() {
+        use NoteIndex::{FromEnd, FromStart};
+
+        assert_eq!(
+            NoteSelector::parse("5"),
+            Ok(NoteSelector::single(FromStart(5)))
+        );
+        assert_eq!(
+            NoteSelector::parse("2,5,7-9"),
+            Ok(ids(&[
+                (FromStart(2), FromStart(2)),
+                (FromStart(5), FromStart(5)),
+                (FromStart(7), FromStart(9))
+            ]))
+        );
+        assert_eq!(
+            NoteSelector::parse("-3--1,last"),
+            Ok(ids(&[(FromEnd(3), FromEnd(1)), (FromEnd(1), FromEnd(1))]))
+        );
+        assert_eq!(
+            NoteSelector::parse("1--2"),
+            Ok(ids(&[(FromStart(1), FromEnd(2))]))
+        );
+
+        for value in ["", "x", "2,", "7-", "-", "1---2"] {
+            assert!(NoteSelector::parse(value).is_err(), "{}", value);
+        }
+    }
+
+    #[test]
+    fn test_resolve_ids() {
+        let notes = notes(&["a", "b", "c", "d"]);
+        let resolve = |value| NoteSelector::parse(value).unwrap().resolve(&notes);
+
+        assert_eq!(resolve("3,1,1"), Ok(vec![1, 3]));
+        assert_eq!(resolve("-2--1"), Ok(vec![2, 3]));
+        assert_eq!(resolve("1-last"), Ok(vec![1, 2, 3]));
+        assert!(resolve("1,4").is_err());
+        assert!(resolve("-5").is_err());
+        assert!(resolve("3-1").is_err());
+        // Ranges are checked against the notes before they are expanded
+        assert!(resolve("0-99999999999").is_err());
+    }
+
+    #[test]
+    fn test_resolve_matching() {
+        let notes = notes(&["rm -rf /tmp/build", "ls", "cat /tmp/log"]);
+
+        assert_eq!(
+            NoteSelector::Matching("tmp".to_string()).resolve(&notes),
+            Ok(vec![0, 2])
+        );
+        assert!(NoteSelector::Matching("ssh".to_string())
+            .resolve(&notes)
+            .is_err());
+    }
+
+    #[test]
+    fn test_display() {
+        let display = |value| NoteSelector::parse(value).unwrap().to_string();
+
+        assert_eq!(display("5"), "note 5");
+        assert_eq!(display("last"), "note -1");
+        assert_eq!(display("2,5-7"), "notes 2, 5-7");
+        assert_eq!(
+            NoteSelector::Matching("tmp".to_string()).to_string(),
+            "notes matching 'tmp'"
+        );
+    }
+}
@@ -0,0 +1,149 @@
+use crate::notefile::Note;
+
+pub const SHELLS: [&str; 3] = ["bash", "zsh", "fish"];
+
+/// Records the previous command and its exit status before each prompt in shell variables,
+/// which the `rn` function below hands only to rn, and binds Alt-s to save that command into
+/// `$RN_LAST_FILE` (default `commands`).
+const BASH_INTEGRATION: &str = r#"# rn shell integration, load with: eval "$(rn init bash)"
+__rn_record_last_command() {
+    __rn_last_status=$?
+    __rn_last_command=$(HISTTIMEFORMAT= builtin fc -ln -0)
+}
+PROMPT_COMMAND="__rn_record_last_command${PROMPT_COMMAND:+; $PROMPT_COMMAND}"
+rn() {
+    RN_LAST_COMMAND="$__rn_last_command" RN_LAST_STATUS="$__rn_last_status" command rn "$@"
+}
+bind -x '"\es": rn "${RN_LAST_FILE:-commands}" last'
+"#;
+
+const ZSH_INTEGRATION: &str = r#"# rn shell integration, load with: eval "$(rn init zsh)"
+__rn_record_last_command() {
+    __rn_last_status=$?
+    __rn_last_command="$(builtin fc -ln -1)"
+}
+precmd_functions=(__rn_record_last_command $precmd_functions)
+rn() {
+    RN_LAST_COMMAND="$__rn_last_command" RN_LAST_STATUS="$__rn_last_status" command rn "$@"
+}
+__rn_save_last_command() {
+    rn "${RN_LAST_FILE:-commands}" last
+    zle reset-prompt
+}
+zle -N __rn_save_last_command
+bindkey '\es' __rn_save_last_command
+"#;
+
+const FISH_INTEGRATION: &str = r#"# rn shell integration, load with: rn init fish | source
+function __rn_record_last_command --on-event fish_postexec
+    set -g __rn_last_status $status
+    set -g __rn_last_command $argv[1]
+end
+function rn
+    env RN_LAST_COMMAND=$__rn_last_command RN_LAST_STATUS=$__rn_last_status rn $argv
+end
+function __rn_save_last_command
+    if set -q RN_LAST_FILE
+        rn $RN_LAST_FILE last
+    else
+        rn commands last
+    end
+    commandline -f repaint
+end
+bind \es __rn_save_last_command
+"#;
+
+pub fn integration_script(shell: &str) -> Option<&'static str> {
+    match shell {
+        "bash" => Some(BASH_INTEGRATION),
+        "zsh" => Some(ZSH_INTEGRATION),
+        "fish" => Some(FISH_INTEGRATION),
+        _ => None,
+    }
+}
+
+/// Builds a note out of the previous command recorded by the shell integration. The exit
+/// status and working directory are only kept when `with_context` is set.
+pub fn last_command_note(
+    command: Option<String>,
+    status: Option<String>,
+    cwd: Option<String>,
+    with_context: bool,
+) -> Result<Note, String> {
+    let command = command.unwrap_or_default();
+    let command = command.trim();
+
+    if command.is_empty() {
+        return Err(
+            "No previous command recorded, load the shell integration with: eval \"$(rn init <shell>)\""
+                .to_string(),
+        );
+    }
+
+    let mut note = Note::new(command);
+
+    if with_context {
+        note.exit_status = status.and_then(|s| s.trim().parse::<i32>().ok());
+        note.cwd = cwd;
+    }
+
+    Ok(note)
+}
+
+#[allow(unused_variables)]
+mod shellintegration_tests {
+    #[allow(unused_imports)]
+    use crate::notefile::Note;
+    #[allow(unused_imports)]
+    use crate::shellintegration::{integration_script, last_command_note, SHELLS};
+
+    #[test]
+    fn test_every_shell_has_integration() {
+        for shell in SHELLS {
+            let script = integration_script(shell).unwrap();
+            assert!(script.contains("RN_LAST_COMMAND"));
+            // The command is only handed to rn, never to every program the shell starts
+            assert!(
+                !script.contains("export") && !script.contains("-gx"),
+                "{}",
+                shell
+            );
+        }
+        assert_eq!(integration_script("tcsh"), None);
+    }
+
+    #[test]
+    fn test_last_command_note() {
+        let note = last_command_note(
+            Some("\tssh-add -l  ".to_string()),
+            Some("0".to_string()),
+            Some("/home/user".to_string()),
+            false,
+        );
+        assert_eq!(note, Ok(Note::new("ssh-add -l")));
+    }
+
+    #[test]
+    fn test_last_command_note_with_context() {
+        let note = last_command_note(
+            Some("make".to_string()),
+            Some("2".to_string()),
+            Some("/home/user".to_string()),
+            true,
+        );
+        assert_eq!(
+            note,
+            Ok(Note {
+                exit_status: Some(2),
+                cwd: Some("/home/user".to_string()),
+                ..Note::new("make")
+            })
+        );
+    }
+
+    #[test]
+    fn test_no_last_command() {
+        assert!(last_command_note(None, None, None, true).is_err());
+        assert!(last_command_note(Some(" ".to_string()), None, None, false).is_err());
+    }
+}
@@ -0,0 +1,205 @@
+use std::env;
+use std::io::{self, IsTerminal};
+
+use serde::Deserialize;
+use terminal_size::{terminal_size_of, Height, Width};
+
+pub const INDEX_COLOR: &str = "\x1b[33m";
+pub const FILE_COLOR: &str = "\x1b[1;34m";
+pub const CONTEXT_COLOR: &str = "\x1b[2m";
+pub const OVERDUE_COLOR: &str = "\x1b[1;31m";
+const RESET: &str = "\x1b[0m";
+
+/// Notes are not wrapped when less room than this is left for them.
+const MIN_WRAP_WIDTH: usize = 20;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ColorChoice {
+    Auto,
+    Always,
+    Never,
+}
+
+impl ColorChoice {
+    pub fn parse(value: &str) -> Result<ColorChoice, String> {
+        match value {
+            "auto" => Ok(ColorChoice::Auto),
+            "always" => Ok(ColorChoice::Always),
+            "never" => Ok(ColorChoice::Never),
+            _ => Err(format!(
+                "Unknown color setting: {}, expected auto, always or never",
+                value
+            )),
+        }
+    }
+}
+
+/// What standard output can show: colors, and when it is a terminal, the width lines are
+/// wrapped to and the number of lines that fit on the screen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Terminal {
+    pub color: bool,
+    pub width: Option<usize>,
+    pub height: Option<usize>,
+}
+
+impl Terminal {
+    /// Colors are used on a terminal unless `$NO_COLOR` is set, or when `choice` asks for
+    /// them either way.
+    pub fn detect(choice: ColorChoice) -> Terminal {
+        let is_terminal = io::stdout().is_terminal();
+        let no_color = env::var_os("NO_COLOR").is_some_and(|value| !value.is_empty());
+        let size = terminal_size_of(io::stdout());
+
+        Terminal {
+            color: match choice {
+                ColorChoice::Always => true,
+                ColorChoice::Never => false,
+                ColorChoice::Auto => is_terminal && !no_color,
+            },
+            width: size.map(|(Width(width), _)| width as usize),
+            height: size.map(|(_, Height(height))| height as usize),
+        }
+    }
+
+    pub fn paint(&self, text: &str, color: &str) -> String {
+        if self.color && !text.is_empty() {
+            format!("{}{}{}", color, text, RESET)
+        } else {
+            text.to_string()
+        }
+    }
+
+    /// Splits a line of text to fit after an indent of `indent` columns, or keeps it whole
+    /// when the width is unknown or too little room is left.
+    pub fn wrap(&self, line: &str, indent: usize) -> Vec<String> {
+        match self.width {
+            Some(width) if width.saturating_sub(indent) >= MIN_WRAP_WIDTH => {
+                wrap(line, width - indent)
+            }
+            _ => vec![line.to_string()],
+        }
+    }
+}
+
+/// Number of columns text takes up, leaving out color escape sequences.
+pub fn visible_width(text: &str) -> usize {
+    let mut width = 0;
+    let mut in_escape = false;
+
+    for c in text.chars() {
+        if in_escape {
+            in_escape = c != 'm';
+        } else if c == '\x1b' {
+            in_escape = true;
+        } else {
+            width += 1;
+        }
+    }
+
+    width
+}
+
+/// Breaks a line into lines of at most `width` characters, at whitespace where possible.
+fn wrap(line: &str, width: usize) -> Vec<String> {
+    let mut lines = Vec::new();
+    let mut rest = line;
+
+    while let Some((split, _)) = rest.char_indices().nth(width) {
+        let break_at = if rest[split..].starts_with(char::is_whitespace) {
+            Some(split)
+        } else {
+            rest[..split].rfind(char::is_whitespace).filter(|&i| i > 0)
+        };
+
+        match break_at {
+            Some(i) => {
+                lines.push(rest[..i].trim_end().to_string());
+                rest = rest[i..].trim_start();
+            }
+            None => {
+                lines.push(rest[..split].to_string());
+                rest = &rest[split..];
+            }
+        }
+    }
+
+    if !rest.is_empty() || lines.is_empty() {
+        lines.push(rest.to_string());
+    }
+
+    lines
+}
+
+#[allow(unused_variables)]
+mod terminal_tests {
+    #[allow(unused_imports)]
+    use crate::terminal::{visible_width, wrap, ColorChoice, Terminal, INDEX_COLOR};
+
+    #[test]
+    fn test_wrap_at_whitespace() {
+        assert_eq!(
+            wrap("ssh -i key user@host and more", 12),
+            vec!["ssh -i key", "user@host", "and more"]
+        );
+        assert_eq!(wrap("short", 12), vec!["short"]);
+        assert_eq!(wrap("", 12), vec![""]);
+    }
+
+    #[test]
+    fn test_wrap_long_words() {
+        assert_eq!(wrap("abcdefghij", 4), vec!["abcd", "efgh", "ij"]);
+        assert_eq!(wrap("ab äöüß", 4), vec!["ab", "äöüß"]);
+    }
+
+    #[test]
+    fn test_wrap_needs_width() {
+        let terminal = Terminal {
+            color: false,
+            width: Some(30),
+            height: None,
+        };
+        let line = "a note that is longer than the room left for it";
+
+        assert_eq!(terminal.wrap(line, 20), vec![line]);
+        assert_eq!(terminal.wrap(line, 5).len(), 2);
+        assert_eq!(
+            Terminal {
+                width: None,
+                ..terminal
+            }
+            .wrap(line, 0),
+            vec![line]
+        );
+    }
+
+    #[test]
+    fn test_paint() {
+        let terminal = Terminal {
+            color: true,
+            width: None,
+            height: None,
+        };
+        let painted = terminal.paint("12", INDEX_COLOR);
+
+        assert_eq!(painted, "\x1b[33m12\x1b[0m");
+        assert_eq!(visible_width(&format!("{}: ", painted)), 4);
+        assert_eq!(
+            Terminal {
+                color: false,
+                ..terminal
+            }
+            .paint("12", INDEX_COLOR),
+            "12"
+        );
+    }
+
+    #[test]
+    fn test_color_choice() {
+        assert_eq!(ColorChoice::parse("never"), Ok(ColorChoice::Never));
+        assert!(ColorChoice::parse("sometimes").is_err());
+        assert!(!Terminal::detect(ColorChoice::Never).color);
+        assert!(Terminal::detect(ColorChoice::Always).color);
+    }
+}